@@ -2,6 +2,7 @@ use {
     crate::state::BOQAccount,
     solana_program::{
         account_info::AccountInfo, 
+        keccak::hashv, 
         program_error::ProgramError, 
         pubkey::Pubkey, 
         msg,
@@ -208,15 +209,17 @@ impl Check {
     }
 
     /// Check that `ata_info` is an associated token account derived from `pda_info` and 
-    /// `token_mint`.
+    /// `token_mint` for the token program `token_program_id`.
     pub fn ata(
         ata_info: &AccountInfo,
         token_mint: &Pubkey,
         wallet: &Pubkey,
+        token_program_id: &Pubkey,
     ) -> Result<(), ProgramError> {
-        let ata = spl_associated_token_account::get_associated_token_address(
+        let ata = spl_associated_token_account::get_associated_token_address_with_program_id(
             &wallet, 
             token_mint,
+            token_program_id,
         );
         Self::account(
             ata_info, 
@@ -224,6 +227,38 @@ impl Check {
         )
     }
 
+    /// Check that `token_program_info` is the SPL Token or Token-2022 program and owns 
+    /// `token_mint_info`.
+    pub fn token_program(
+        token_program_info: &AccountInfo,
+        token_mint_info: &AccountInfo,
+    ) -> Result<(), ProgramError> {
+        spl_token_2022::check_spl_token_program_account(token_program_info.key)?;
+        Self::owner(token_mint_info, token_program_info.key)
+    }
+
+    /// Check that `proof` proves that `leaf` is part of the merkle tree with root `root`. Each 
+    /// pair of nodes is hashed in sorted order.
+    pub fn merkle_proof(
+        proof: &[[u8; 32]],
+        root: &[u8; 32],
+        leaf: &[u8; 32],
+    ) -> Result<(), ProgramError> {
+        let node = proof.iter().fold(*leaf, |node, sibling| {
+            if node <= *sibling {
+                hashv(&[&node, sibling]).to_bytes()
+            } else {
+                hashv(&[sibling, &node]).to_bytes()
+            }
+        });
+        if node.ne(root) {
+            msg!("Invalid merkle proof");
+            Err(ProgramError::InvalidArgument)
+        } else {
+            Ok(())
+        }
+    }
+
     /// Check that `account` has been initialized.
     pub fn initialized(
         account: &impl BOQAccount,
@@ -3,10 +3,14 @@ use {
         decode_error::DecodeError, 
         program_error::ProgramError,
     },
+    thiserror::Error,
 };
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum BOQError {}
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum BOQError {
+    #[error("Insufficient vault balance")]
+    InsufficientVaultBalance,
+}
 
 impl From<BOQError> for ProgramError {
     fn from(e: BOQError) -> Self {
@@ -3,6 +3,11 @@ use {
         BorshDeserialize, 
         BorshSerialize, 
     },
+    crate::state::{
+        BOQEmployerConfig,
+        BOQShift,
+        BOQSplit,
+    },
     solana_program::{
         pubkey::Pubkey, 
         slot_history::Slot, 
@@ -60,6 +65,7 @@ pub enum BOQInstruction {
         start_slot: Option<Slot>,
         slots_per_shift: Option<u64>,
         base_rate_per_slot: Option<u64>,
+        config: BOQEmployerConfig,
     },
 
     // EMPLOYEE
@@ -98,9 +104,193 @@ pub enum BOQInstruction {
     },
     
     /**
-     * Work shift.
+     * Work shift, crediting the wage to the shift account's claimable balance.
      */
     Shift {
         number_of_employees: u8,
     },
+
+    // VAULT
+
+    /**
+     * Transfer `amount` tokens into the employer's vault.
+     */
+    FundEmployer {
+        amount: u64,
+    },
+
+    // STAKING
+
+    /**
+     * Stake an NFT by escrowing it in its employee's vault (custodial) or freezing it in the 
+     * staker's wallet (freeze).
+     */
+    Stake,
+
+    /**
+     * Pay out the employee's available wage and return (custodial) or thaw (freeze) the staked NFT.
+     */
+    Unstake,
+
+    /**
+     * Commit a staked NFT to the employer's lock-up `tier` in exchange for a multiplier on its 
+     * base rate.
+     */
+    Lock {
+        tier: u8,
+    },
+
+    // WITHDRAW
+
+    /**
+     * Pay out a shift account's claimable balance to its owner.
+     */
+    Withdraw,
+
+    // VESTING
+
+    /**
+     * Create vesting PDA account.
+     */
+    CreateVesting {
+        bump: u8,
+    },
+
+    /**
+     * Initialize vesting PDA account.
+     */
+    InitializeVesting {
+        bump: u8,
+        owner: Pubkey,
+    },
+
+    /**
+     * Pay out the vested rewards of a shift account's owner.
+     */
+    ClaimVested,
+
+    // RARITY
+
+    /**
+     * Create rarity table PDA account.
+     */
+    CreateRarityTable {
+        bump: u8,
+    },
+
+    /**
+     * Initialize rarity table PDA account.
+     */
+    InitializeRarityTable {
+        bump: u8,
+        merkle_root: [u8; 32],
+    },
+
+    /**
+     * Set [merkle_root] as the rarity table's root of NFT mint to multiplier leaves.
+     */
+    SetRarityTable {
+        merkle_root: [u8; 32],
+    },
+
+    /**
+     * Assign an employee's rarity `multiplier` with a merkle `proof` against the rarity table.
+     */
+    AssignRarity {
+        multiplier: u16,
+        proof: Vec<[u8; 32]>,
+    },
+
+    // SCHEDULE
+
+    /**
+     * Create schedule PDA account.
+     */
+    CreateSchedule {
+        bump: u8,
+    },
+
+    /**
+     * Initialize schedule PDA account.
+     */
+    InitializeSchedule {
+        bump: u8,
+    },
+
+    /**
+     * Schedule a bonus window at `index`, replacing the window previously scheduled there.
+     */
+    SetBonusWindow {
+        index: u8,
+        start_slot: Slot,
+        end_slot: Slot,
+        multiplier: u16,
+    },
+
+    // CHECKPOINT
+
+    /**
+     * Credit the employee's pending wage to its previous holder if the NFT has changed hands.
+     */
+    Checkpoint,
+
+    // RATES
+
+    /**
+     * Set [base_rate_per_slot] as the employer's base rate from the current slot onwards.
+     */
+    SetBaseRate {
+        base_rate_per_slot: u64,
+    },
+
+    // BOOST
+
+    /**
+     * Burn the employer's boost cost in `salary` tokens to boost an employee's base rate.
+     */
+    Boost,
+
+    // SPLITS
+
+    /**
+     * Set [splits] as the recipients of shares of a shift account's withdrawals.
+     */
+    SetSplits {
+        splits: [BOQSplit; BOQShift::MAX_SPLITS],
+    },
+
+    // RENTAL
+
+    /**
+     * Create rental PDA account.
+     */
+    CreateRental {
+        bump: u8,
+    },
+
+    /**
+     * Initialize rental PDA account.
+     */
+    InitializeRental {
+        bump: u8,
+    },
+
+    /**
+     * Lend an employee to [operator] from [start_slot] to [end_slot], paying [owner_share] of the 
+     * operator's wage to the NFT holder.
+     */
+    SetRental {
+        operator: Pubkey,
+        start_slot: Slot,
+        end_slot: Slot,
+        owner_share: u16,
+    },
+
+    // MIGRATION
+
+    /**
+     * Migrate an employer, employee or shift account created before account layouts were 
+     * versioned to the current layout.
+     */
+    Migrate,
 }
\ No newline at end of file
@@ -1,4 +1,32 @@
-use mpl_token_metadata::state::{Metadata, TokenMetadataAccount};
+use mpl_token_metadata::{
+    instruction::{
+        builders::{
+            DelegateBuilder, 
+            LockBuilder, 
+            RevokeBuilder, 
+            TransferBuilder, 
+            UnlockBuilder,
+            UpdateBuilder,
+        },
+        DelegateArgs, 
+        InstructionBuilder, 
+        MetadataDelegateRole, 
+        LockArgs, 
+        RevokeArgs, 
+        TransferArgs, 
+        UnlockArgs,
+        UpdateArgs,
+    },
+    pda::find_metadata_delegate_record_account,
+    state::{
+        Data, 
+        Metadata, 
+        ProgrammableConfig, 
+        TokenMetadataAccount, 
+        TokenStandard, 
+        MAX_URI_LENGTH,
+    },
+};
 
 use {
     borsh::{
@@ -7,6 +35,7 @@ use {
     },
     crate::{
         check::Check,
+        error::BOQError,
         instruction::BOQInstruction,
         state::*,
     },
@@ -15,21 +44,28 @@ use {
             next_account_info, 
             AccountInfo, 
         },
+        program_option::COption,
         clock::Clock,
         entrypoint::ProgramResult,
-        program::{invoke_signed},
-        program_pack::Pack, 
+        msg,
+        program::{invoke, invoke_signed},
+        program_error::ProgramError,
         pubkey::Pubkey,
         rent::Rent, 
         slot_history::{Slot},
         sysvar::Sysvar, 
         system_instruction,
     },
-    spl_token::state::{
-        Account, 
+    spl_token_2022::{
+        extension::StateWithExtensions,
+        state::{
+            Account, 
+            Mint,
+        },
     },
     std::{
         cmp::min, 
+        slice::Iter,
     }
 };
 
@@ -99,6 +135,7 @@ impl Processor {
                 start_slot, 
                 slots_per_shift, 
                 base_rate_per_slot, 
+                config,
              } => {
                 Self::process_initialize_employer(
                     program_id, 
@@ -111,6 +148,7 @@ impl Processor {
                     start_slot,
                     slots_per_shift,
                     base_rate_per_slot,
+                    config,
                 )
              },
 
@@ -165,7 +203,223 @@ impl Processor {
                     accounts,
                     number_of_employees,
                 )
-             }
+             },
+
+             BOQInstruction::FundEmployer {
+                amount,
+             } => {
+                Self::process_fund_employer(
+                    program_id, 
+                    accounts,
+                    amount,
+                )
+             },
+
+             BOQInstruction::Stake => {
+                Self::process_stake(
+                    program_id, 
+                    accounts,
+                )
+             },
+             BOQInstruction::Unstake => {
+                Self::process_unstake(
+                    program_id, 
+                    accounts,
+                )
+             },
+             BOQInstruction::Lock {
+                tier,
+             } => {
+                Self::process_lock(
+                    program_id, 
+                    accounts,
+                    tier,
+                )
+             },
+
+             BOQInstruction::Withdraw => {
+                Self::process_withdraw(
+                    program_id, 
+                    accounts,
+                )
+             },
+
+             BOQInstruction::CreateVesting {
+                bump,
+             } => {
+                Self::process_create_vesting(
+                    program_id, 
+                    accounts,
+                    bump,
+                )
+             },
+             BOQInstruction::InitializeVesting {
+                bump,
+                owner,
+             } => {
+                Self::process_initialize_vesting(
+                    program_id, 
+                    accounts,
+                    bump,
+                    &owner,
+                )
+             },
+             BOQInstruction::ClaimVested => {
+                Self::process_claim_vested(
+                    program_id, 
+                    accounts,
+                )
+             },
+
+             BOQInstruction::CreateRarityTable {
+                bump,
+             } => {
+                Self::process_create_rarity_table(
+                    program_id, 
+                    accounts,
+                    bump,
+                )
+             },
+             BOQInstruction::InitializeRarityTable {
+                bump,
+                merkle_root,
+             } => {
+                Self::process_initialize_rarity_table(
+                    program_id, 
+                    accounts,
+                    bump,
+                    merkle_root,
+                )
+             },
+             BOQInstruction::SetRarityTable {
+                merkle_root,
+             } => {
+                Self::process_set_rarity_table(
+                    program_id, 
+                    accounts,
+                    merkle_root,
+                )
+             },
+             BOQInstruction::AssignRarity {
+                multiplier,
+                proof,
+             } => {
+                Self::process_assign_rarity(
+                    program_id, 
+                    accounts,
+                    multiplier,
+                    &proof,
+                )
+             },
+
+             BOQInstruction::CreateSchedule {
+                bump,
+             } => {
+                Self::process_create_schedule(
+                    program_id, 
+                    accounts,
+                    bump,
+                )
+             },
+             BOQInstruction::InitializeSchedule {
+                bump,
+             } => {
+                Self::process_initialize_schedule(
+                    program_id, 
+                    accounts,
+                    bump,
+                )
+             },
+             BOQInstruction::SetBonusWindow {
+                index,
+                start_slot,
+                end_slot,
+                multiplier,
+             } => {
+                Self::process_set_bonus_window(
+                    program_id, 
+                    accounts,
+                    index,
+                    start_slot,
+                    end_slot,
+                    multiplier,
+                )
+             },
+
+             BOQInstruction::Checkpoint => {
+                Self::process_checkpoint(
+                    program_id, 
+                    accounts,
+                )
+             },
+
+             BOQInstruction::SetBaseRate {
+                base_rate_per_slot,
+             } => {
+                Self::process_set_base_rate(
+                    program_id, 
+                    accounts,
+                    base_rate_per_slot,
+                )
+             },
+
+             BOQInstruction::Boost => {
+                Self::process_boost(
+                    program_id, 
+                    accounts,
+                )
+             },
+
+             BOQInstruction::SetSplits {
+                splits,
+             } => {
+                Self::process_set_splits(
+                    program_id, 
+                    accounts,
+                    splits,
+                )
+             },
+
+             BOQInstruction::CreateRental {
+                bump,
+             } => {
+                Self::process_create_rental(
+                    program_id, 
+                    accounts,
+                    bump,
+                )
+             },
+             BOQInstruction::InitializeRental {
+                bump,
+             } => {
+                Self::process_initialize_rental(
+                    program_id, 
+                    accounts,
+                    bump,
+                )
+             },
+             BOQInstruction::SetRental {
+                operator,
+                start_slot,
+                end_slot,
+                owner_share,
+             } => {
+                Self::process_set_rental(
+                    program_id, 
+                    accounts,
+                    &operator,
+                    start_slot,
+                    end_slot,
+                    owner_share,
+                )
+             },
+
+             BOQInstruction::Migrate => {
+                Self::process_migrate(
+                    program_id, 
+                    accounts,
+                )
+             },
         }
     }
 
@@ -314,13 +568,14 @@ impl Processor {
 
         // The token_program.
         let token_program_info = next_account_info(account_info_iter)?;
+        Check::token_program(token_program_info, token_mint_info)?;
 
         invoke_signed(
-            &spl_token::instruction::set_authority(
+            &spl_token_2022::instruction::set_authority(
                 token_program_info.key, 
                 token_mint_info.key, 
                 Some(&new_authority_pubkey), 
-                spl_token::instruction::AuthorityType::MintTokens, 
+                spl_token_2022::instruction::AuthorityType::MintTokens, 
                 mint_authority_info.key, 
                 &[],
             )?, 
@@ -355,7 +610,8 @@ impl Processor {
     }
 
     /**
-     * Initializes the [BOQEmployer] account.
+     * Initializes the [BOQEmployer] account. The employer's optional features are set by `config` 
+     * ([BOQEmployerConfig::default] leaves them all off).
      * 
      * This instruction does not check for signers and MUST be sent in the same transaction as 
      * [BOQInstruction::CreateEmployer] to prevent another account from taking control.
@@ -373,6 +629,7 @@ impl Processor {
         start_slot: Option<Slot>,
         slots_per_shift: Option<u64>,
         base_rate_per_slot: Option<u64>,
+        config: BOQEmployerConfig,
     ) -> ProgramResult {
         
         // The `employer` account.
@@ -384,6 +641,24 @@ impl Processor {
         // The Sysvar clock.
         let clock = Clock::get()?;
 
+        // Check that at most the whole balance is locked for vesting.
+        let vesting_rate = u64::from(config.vesting_rate);
+        Check::assert(vesting_rate <= MAX_BASIS_POINTS, "Invalid vesting rate.")?;
+
+        // Check that the base rate decreases with each decay period.
+        let decay_rate = u64::from(config.decay_rate);
+        Check::assert(decay_rate < MAX_BASIS_POINTS, "Invalid decay rate.")?;
+
+        // Check that fees are paid to a treasury.
+        let fee_rate = u64::from(config.fee_rate);
+        Check::assert(fee_rate <= MAX_BASIS_POINTS, "Invalid fee rate.")?;
+        let has_treasury = config.treasury != Pubkey::default();
+        Check::assert(fee_rate == 0 || has_treasury, "Missing treasury.")?;
+
+        // Check that referrers earn at most the referred shift's earnings.
+        let referral_rate = u64::from(config.referral_rate);
+        Check::assert(referral_rate <= MAX_BASIS_POINTS, "Invalid referral rate.")?;
+
         // Set account data.
         BOQEmployer::new(
             bump,
@@ -395,6 +670,7 @@ impl Processor {
             base_rate_per_slot.unwrap_or(100_000),
             *token_mint,
             *collection_mint,
+            config,
         ).serialize(
             &mut &mut employer_data[..],
         )?;
@@ -402,6 +678,42 @@ impl Processor {
         Ok(())
     }
 
+    /**
+     * Sets `base_rate_per_slot` as the employer's base rate from the current slot onwards. Slots 
+     * worked before the change are still paid at the previous rate.
+     * 
+     * `Must be signed by the shift program`.
+     * 
+     * Throws a [ProgramError] if the rate history is full.
+     */
+    fn process_set_base_rate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        base_rate_per_slot: u64,
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The `employer` PDA account.
+        let employer_info = next_account_info(account_info_iter)?;
+        Check::owner(&employer_info, program_id)?;
+        let mut employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+
+        // The shift program account.
+        let shift_program_info = next_account_info(account_info_iter)?;
+        Check::signer(shift_program_info)?;
+        Check::pubkey(shift_program_info.key, program_id)?;
+
+        let slot = Clock::get()?.slot;
+        let is_set = employer.set_base_rate(base_rate_per_slot, slot);
+        Check::assert(is_set, "Rate history full.")?;
+        employer.serialize(&mut &mut employer_info.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
     /**
      * Creates the PDA for [BOQEmployee].
      * 
@@ -429,6 +741,16 @@ impl Processor {
             Check::owner(nft_metadata_info, &mpl_token_metadata::ID)?;
             let nft_metadata = Metadata::from_account_info(nft_metadata_info)?;
             Check::pubkey(&nft_metadata.mint, nft_token_info.key)?;
+            let token_standard = nft_metadata.token_standard.unwrap_or(TokenStandard::NonFungible);
+            Check::assert(
+                matches!(
+                    token_standard, 
+                    TokenStandard::NonFungible 
+                    | TokenStandard::NonFungibleEdition 
+                    | TokenStandard::ProgrammableNonFungible
+                ), 
+                "Invalid token standard.",
+            )?;
             let collection = nft_metadata.collection.unwrap();
             Check::assert(collection.verified, "Unverified collection.")?;
             Check::pubkey(&collection.key, &employer.collection_mint)?; 
@@ -440,6 +762,9 @@ impl Processor {
                 &[BOQSeed::EMPLOYEE.as_ref(), nft_token_info.key.as_ref(), &[bump]],
                 false,
             )?;
+
+            // Distribute the pool's rewards to the existing employees before adding a new share.
+            employer.update_pool(Clock::get()?.slot);
             employer.employees += 1;
             employer.serialize(&mut &mut employer_data[..])?;
         } 
@@ -470,6 +795,8 @@ impl Processor {
     /**
      * Initializes a [BOQEmployee] account.
      * 
+     * The employee's share of the employer's pool starts accruing from the current pool rewards.
+     * 
      * This instruction does not check for signers and MUST be sent in the same transaction as 
      * [BOQInstruction::CreateEmployee] to prevent another account from taking control.
      * 
@@ -491,8 +818,18 @@ impl Processor {
         let seeds = &[BOQSeed::EMPLOYEE.as_ref(), nft_mint.as_ref(), &[bump]];
         Check::pda(program_id, employee_info, seeds)?;
 
+        // The `employer` PDA account.
+        let employer_info = &accounts[1];
+        Check::owner(employer_info, program_id)?;
+        let employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+
         // Set account data.
-        BOQEmployee::new(bump, *nft_mint).serialize(&mut &mut employee_data[..])?;
+        BOQEmployee::new(
+            bump, 
+            *nft_mint, 
+            employer.pool_reward_per_employee,
+        ).serialize(&mut &mut employee_data[..])?;
 
         Ok(())
     }
@@ -500,6 +837,9 @@ impl Processor {
     /**
      * Creates a [BOQShift] PDA account.
      * 
+     * Only the canonical bump is accepted, so that each owner has a single shift account (see 
+     * [Processor::_checkpoint]).
+     * 
      * Throws a [ProgramError] if the account already exists.
      */
     fn process_create_shift(
@@ -508,6 +848,11 @@ impl Processor {
         bump: u8,
     ) -> ProgramResult {
         let owner_info = &accounts[0];
+        let (_, shift_bump) = Pubkey::find_program_address(
+            &[BOQSeed::SHIFT.as_ref(), owner_info.key.as_ref()], 
+            program_id,
+        );
+        Check::assert(bump == shift_bump, "Invalid shift bump.")?;
         Self::_process_create_pda(
             program_id, 
             accounts, 
@@ -602,38 +947,89 @@ impl Processor {
     /**
      * Initializes a [BOQShift] account.
      * 
+     * An optional referrer's shift account may follow the shift account. It is recorded as the 
+     * shift account's referrer and earns the employer's `referral_rate` of the shift account's 
+     * earnings for the employer's `referral_shifts` shifts from its first paid shift (see 
+     * [Processor::process_shift]).
+     * 
      * This instruction does not check for signers and MUST be sent in the same transaction as 
      * [BOQInstruction::CreateShift] to prevent another account from taking control.
      * 
-     * Throws a [ProgramError] if the account has already been initialized.
+     * Throws a [ProgramError] if the account has already been initialized or the referrer is the 
+     * shift owner.
      */
     fn process_initialize_shift(
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
         accounts: &[AccountInfo],
         bump: u8,
         slot: Slot,
         owner: &Pubkey,
     ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
         
         // The `shift` PDA account to initialize.
-        let shift_info = &accounts[0];
+        let shift_info = next_account_info(account_info_iter)?;
         let shift_data = &mut shift_info.data.borrow_mut();
         let shift = BOQShift::try_from_slice(&shift_data)?;
         Check::uninitialized(&shift, shift_info)?;
 
         // Set account data.
-        BOQShift::new(bump, slot, *owner).serialize(&mut &mut shift_data[..])?;
+        let mut shift = BOQShift::new(bump, slot, *owner);
+
+        // The referrer's `shift` account.
+        if let Some(referrer_info) = account_info_iter.next() {
+            Check::owner(&referrer_info, program_id)?;
+            let referrer = BOQShift::try_from_slice(&referrer_info.data.borrow())?;
+            Check::initialized(&referrer, referrer_info)?;
+            Check::assert(referrer.owner.ne(owner), "Invalid referrer.")?;
+            shift.referrer = *referrer_info.key;
+        }
+
+        shift.serialize(&mut &mut shift_data[..])?;
 
         Ok(())
     }
 
     /**
-     * Pays out the available wage to the current NFT holder.
+     * Credits the available wage of the current NFT holder to the claimable balance of its shift 
+     * account. The balance is paid out by [BOQInstruction::Withdraw].
+     * 
+     * If an employee's NFT has changed hands since it last worked, the wage accrued under the 
+     * previous holder is first credited to the previous holder's shift account, which must follow 
+     * the employee's PDA account (see [Processor::process_checkpoint]).
+     * 
+     * In [BOQStakingMode::Custodial] only employees whose NFT is escrowed are paid, and the wage 
+     * goes to the recorded staker. In [BOQStakingMode::Freeze] only employees whose NFT is frozen 
+     * by the employee PDA are paid.
+     * 
+     * If an employee has a rental account, it follows the employee's accounts (see 
+     * [Processor::process_set_rental]). While the rental is active the employee is only paid to 
+     * the operator's shift account, and the NFT holder's shift account must follow to receive the 
+     * holder's share. The rental owner's shift account must also follow the previous holder's 
+     * shift account when an operator's wage is settled, unless the shift account belongs to the 
+     * rental owner.
+     * 
+     * If at least the employer's `crew_size` employees work together, the employer's 
+     * `crew_bonus_rate` of their wage is credited as a bonus.
+     * 
+     * If the shift account was referred, the referrer's shift account follows the employees' 
+     * accounts for the employer's `referral_shifts` shifts of `slots_per_shift` slots from the 
+     * shift account's first paid shift, however often it is paid. The referrer is credited the 
+     * employer's `referral_rate` of the shift's earnings on top of them, up to the employer's 
+     * `max_referral_rewards` per referrer.
+     * 
+     * If the employer updates NFT metadata, the token metadata accounts follow the shift account 
+     * and each employee's NFT mint and metadata accounts follow its PDA account. When an employee 
+     * levels up, its NFT's URI is updated by the employer PDA, which must be a data delegate of 
+     * the collection (see [Processor::_process_update_level]).
      * 
      * This instruction does not check for signers as the NFT holder is always the correct recipient 
      * of the available payment.
      * 
-     * Throws a [ProgramError] for an invalid request.
+     * Throws a [ProgramError] for an invalid request, including the same employee being provided 
+     * more than once.
      */
     fn process_shift(
         program_id: &Pubkey,
@@ -644,18 +1040,12 @@ impl Processor {
         // The instruction accounts.
         let account_info_iter = &mut accounts.iter();
 
-        // The `mint authority` PDA account.
-        let mint_authority_info = next_account_info(account_info_iter)?;
-        Check::owner(&mint_authority_info, program_id)?;
-        let mint_authority = BOQMintAuthority::try_from_slice(&mint_authority_info.data.borrow())?;
-        Check::initialized(&mint_authority, mint_authority_info)?;
-        let mint_seeds = &[BOQSeed::MINT_AUTHORITY.as_bytes(), &[mint_authority.bump]];
-
         // The `employer` PDA account.
         let employer_info = next_account_info(account_info_iter)?;
         Check::owner(&employer_info, program_id)?;
-        let employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        let mut employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
         Check::initialized(&employer, employer_info)?;
+        Check::assert(employer.is_active, "Employer not active.")?;
 
         // A `shift` account.
         let shift_info = next_account_info(account_info_iter)?;
@@ -664,147 +1054,3020 @@ impl Processor {
         let mut shift = BOQShift::try_from_slice(&shift_data)?;
         Check::initialized(&shift, shift_info)?;
 
-        // The `salary` token's mint account.
-        let token_mint_info = next_account_info(account_info_iter)?;
-        Check::pubkey(token_mint_info.key, &employer.token_mint)?;
-
-        // The `salary` token's receiver account.
-        let ata_info = next_account_info(account_info_iter)?;
-        Check::ata(ata_info, token_mint_info.key, &shift.owner)?;
-
         // // The shift program.
         // let shift_program_info = next_account_info(account_info_iter)?;
         // Check::pubkey(shift_program_info.key, program_id)?;
 
-        // The token program.
-        let token_program_info = next_account_info(account_info_iter)?;
+        // The bonus window schedule.
+        let schedule = Self::_next_schedule(program_id, account_info_iter, &employer)?;
+
+        // The token metadata accounts used to update the employees' NFT metadata.
+        let metadata_infos = if employer.update_metadata {
+
+            // The token metadata program.
+            let token_metadata_program_info = next_account_info(account_info_iter)?;
+            Check::pubkey(token_metadata_program_info.key, &mpl_token_metadata::ID)?;
+
+            // The employer's data delegate record for the collection.
+            let delegate_record_info = next_account_info(account_info_iter)?;
+
+            // The system program.
+            let system_program_info = next_account_info(account_info_iter)?;
+
+            // The instructions sysvar account.
+            let sysvar_instructions_info = next_account_info(account_info_iter)?;
+
+            Some([
+                token_metadata_program_info, 
+                delegate_record_info, 
+                system_program_info, 
+                sysvar_instructions_info,
+            ])
+        } else {
+            None
+        };
 
         // Get the slot information.
         let slot = Clock::get()?.slot;
         let start_slot = employer.start_slot;
         let end_slot = employer.end_slot;
-        let slots_per_shift = employer.slots_per_shift;
 
         // Check that the employer is still running.
         let active_message = "Mining not available.";
         Check::assert(slot >= start_slot && slot <= end_slot, active_message)?;
 
-        let mut total_amount = 0;
+        // Distribute the pool's rewards up to the current slot.
+        employer.update_pool(slot);
+
+        // The employees working this shift.
+        let mut crew: Vec<Pubkey> = Vec::with_capacity(usize::from(number_of_employees));
+        let mut crew_amount = 0;
 
         for _i in 0..number_of_employees {
             
-            // The user's NFT token account.
+            // The user's NFT token account (the employee's escrow account in custodial mode).
             let nft_token_info = next_account_info(account_info_iter)?;
 
             // The `employee` PDA account.
             let employee_info = next_account_info(account_info_iter)?;
 
             // Unpack the NFT SPL token.
-            let nft_token = Account::unpack(&nft_token_info.data.borrow())?;
-
-            // Check that `nft_token_info` is potentially an NFT (amount == 1) and is owned by the 
-            // provided shift account.
-            if nft_token.amount == 1 && nft_token.owner.eq(&shift.owner)  {
-
-                // Check that `employee_info` is a valid PDA account.
-                Check::owner(&employee_info, program_id)?;
-                let employee_data = &mut employee_info.data.borrow_mut();
-                let mut employee = BOQEmployee::try_from_slice(&employee_data)?;
-                Check::initialized(&employee, employee_info)?;
-
-                // Check that the provided NFT token account and employee PDA account are for the 
-                // same token mint.
-                Check::pubkey(&nft_token.mint, &employee.nft_mint)?;
-
-                // Calculate the base rate.
-                let elapsed_slots = slot - employee.last_slot;
-                let available_slots = min(elapsed_slots, slots_per_shift);
-                let total_slots = employee.total_slots;
-                if available_slots > 0 {
+            let nft_token = Self::_unpack_token_account(nft_token_info)?;
+
+            // Check that `employee_info` is a valid PDA account.
+            Check::owner(&employee_info, program_id)?;
+            let employee_data = &mut employee_info.data.borrow_mut();
+            let mut employee = BOQEmployee::try_from_slice(&employee_data)?;
+            Check::initialized(&employee, employee_info)?;
+
+            // Check that the employee is only counted once.
+            Check::assert(!crew.contains(employee_info.key), "Duplicate employee.")?;
+
+            // The NFT's mint and metadata accounts.
+            let nft_infos = match metadata_infos {
+                Some(_) => {
+                    let nft_mint_info = next_account_info(account_info_iter)?;
+                    Check::pubkey(nft_mint_info.key, &employee.nft_mint)?;
+                    let nft_metadata_info = next_account_info(account_info_iter)?;
+                    Some([nft_mint_info, nft_metadata_info])
+                },
+                None => None,
+            };
+
+            // The employee's rental agreement.
+            let rental = Self::_next_rental(
+                program_id, 
+                account_info_iter, 
+                employee_info, 
+                &employee,
+            )?;
 
-                    let base_rate = employer.base_rate_per_slot * available_slots;
-                    let employee_total_slots = total_slots + available_slots;
+            // Check that the provided NFT token account and employee PDA account are for the 
+            // same token mint.
+            Check::pubkey(&nft_token.mint, &employee.nft_mint)?;
 
-                    // Calculate the inflation rate.
-                    let inflation_rate = if employee_total_slots < slots_per_shift {
-                        0
-                    } else {
-                        let current_shift = total_slots / slots_per_shift;
-                        let next_shift = current_shift + 1;
-                        let shift_boundary = next_shift * slots_per_shift;
-                        let next_shift_slots = if employee_total_slots > shift_boundary { 
-                            employee_total_slots % slots_per_shift
-                        } else {
-                            0
-                        };
-                        let current_shift_slots = available_slots - next_shift_slots;
-                        (employer.inflation_rate_per_slot * current_shift_slots * current_shift)
-                        + (employer.inflation_rate_per_slot * next_shift_slots * next_shift)
-                    };
+            // Check that `nft_token_info` is potentially an NFT (amount == 1) and is worked for 
+            // the owner of the provided shift account, either as its holder or as its operator.
+            let holder = Self::_holder(&employer, employee_info, &employee, &nft_token);
+            let worker = Self::_worker(rental.as_ref(), holder, slot);
+            if worker == Some(shift.owner) {
+                let owner_amount = Self::_checkpoint(
+                    program_id, 
+                    account_info_iter, 
+                    &mut employer, 
+                    schedule.as_ref(), 
+                    rental.as_ref(), 
+                    &mut employee, 
+                    &shift.owner, 
+                    slot,
+                )?;
+                if let (Some(rental), Some(owner_amount)) = (rental.as_ref(), owner_amount) {
+                    Self::_credit_rental_owner(
+                        program_id, 
+                        account_info_iter, 
+                        rental, 
+                        Some(&mut shift), 
+                        owner_amount,
+                    )?;
+                }
+                let level = employee.level;
+                let mut amount = Self::_work_shift(
+                    &mut employer, 
+                    schedule.as_ref(), 
+                    &mut employee, 
+                    &mut shift, 
+                    slot,
+                );
+
+                // Credit the NFT holder's share of the operator's wage.
+                if let (Some(rental), true) = (rental.as_ref(), worker != holder) {
+                    let owner_amount = rental.owner_amount(amount);
+                    Self::_credit_rental_owner(
+                        program_id, 
+                        account_info_iter, 
+                        rental, 
+                        None, 
+                        owner_amount,
+                    )?;
+                    shift.total_rewards -= owner_amount;
+                    amount -= owner_amount;
+                }
 
-                    // // Calculate the bonus.
-                    // let employee_shifts = employee.total_shifts(&employer);
-                    // let completed_shifts = min(employee_shifts, current_shift);
-                    // let bonus_rate = completed_shifts * employer.rate_increase_per_shift;
-
-                    let amount = base_rate + inflation_rate;
-
-                    shift.total_slots += available_slots;
-                    shift.total_rewards += amount;
-                    shift.serialize(&mut &mut shift_data[..])?;
-
-                    employee.last_slot = slot;
-                    employee.total_slots = employee_total_slots;
-                    employee.serialize(&mut &mut employee_data[..])?;
-
-                    total_amount += amount;
-
-                    // let ix = spl_token::instruction::mint_to(
-                    //     &spl_token::ID, 
-                    //     &token_mint_info.key, 
-                    //     &owner_info.key, 
-                    //     &shift_program_info.key, 
-                    //     &[], 
-                    //     amount,
-                    // )?;
-                    // invoke_signed(
-                    //     &ix, 
-                    //     &[
-                    //         token_program_info.clone(),
-                    //         token_mint_info.clone(),
-                    //         owner_info.clone(),
-                    //         shift_program_info.clone(),
-                    //     ], 
-                    //     &[
-
-                    //     ],
-                    // )
+                shift.claimable += amount;
+                employee.serialize(&mut &mut employee_data[..])?;
+                crew.push(*employee_info.key);
+                crew_amount += amount;
+
+                // Show the new level on the NFT.
+                if let (Some(metadata_infos), Some(nft_infos)) = (metadata_infos, nft_infos) {
+                    if employee.level > level {
+                        Self::_process_update_level(
+                            employer_info, 
+                            &employer, 
+                            metadata_infos, 
+                            nft_infos, 
+                            employee.level,
+                        )?;
+                    }
                 }
             }
         }
 
-        if total_amount > 0 {
-            invoke_signed(
-                &spl_token::instruction::mint_to(
-                    token_program_info.key, 
-                    token_mint_info.key, 
-                    ata_info.key, 
-                    mint_authority_info.key, 
-                    &[], 
-                    total_amount, 
-                )?,
-                &[
-                    token_mint_info.clone(),
-                    ata_info.clone(),
-                    mint_authority_info.clone(),
-                    token_program_info.clone(),
-                ],
-                &[
-                    mint_seeds,
-                ]
-            )
-        } else {
-            Ok(())
+        // Credit the crew bonus.
+        let crew_size = u8::try_from(crew.len()).unwrap();
+        let bonus = employer.emit(employer.crew_bonus_amount(crew_size, crew_amount));
+        shift.total_rewards += bonus;
+        shift.claimable += bonus;
+
+        // Credit the referrer's reward on top of the shift's earnings.
+        let earned = crew_amount + bonus;
+        if shift.is_referred(&employer, slot) {
+
+            // The referrer's `shift` account.
+            let referrer_info = next_account_info(account_info_iter)?;
+            Check::pubkey(referrer_info.key, &shift.referrer)?;
+            Check::owner(&referrer_info, program_id)?;
+            let referrer_data = &mut referrer_info.data.borrow_mut();
+            let mut referrer = BOQShift::try_from_slice(&referrer_data)?;
+            Check::initialized(&referrer, referrer_info)?;
+
+            // The referral period starts with the first shift that earns tokens.
+            if earned > 0 {
+                if shift.referral_start_slot == 0 {
+                    shift.referral_start_slot = slot;
+                }
+                let amount = employer.referral_amount(earned, referrer.referral_rewards);
+                let reward = employer.emit(amount);
+                referrer.total_rewards += reward;
+                referrer.claimable += reward;
+                referrer.referral_rewards += reward;
+                referrer.serialize(&mut &mut referrer_data[..])?;
+            }
         }
+
+        // Update the shift's totals.
+        shift.serialize(&mut &mut shift_data[..])?;
+
+        // Update the employer's emission totals.
+        employer.serialize(&mut &mut employer_info.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /**
+     * Pays out the claimable balance of a shift account to its owner's associated token account, 
+     * creating the token account if it does not exist.
+     * 
+     * If the employer locks rewards for vesting, the owner's vesting account must follow the 
+     * associated token program. The employer's `vesting_rate` of the balance is locked in a 
+     * tranche of its own, which unlocks linearly over the employer's `vesting_slots`, and any 
+     * previously locked tokens that have unlocked are paid out with the rest of the balance.
+     * 
+     * If the employer charges a fee, the employer's treasury token account must follow, then the 
+     * token account of each recipient in the shift's split table (see 
+     * [Processor::_process_withdrawal]).
+     * 
+     * This instruction only checks that the owner signs if the employer locks rewards for vesting, 
+     * as each withdrawal takes one of the owner's [BOQVesting::MAX_TRANCHES] vesting tranches. 
+     * Otherwise the balance is always paid to the owner. The signer only pays for the token 
+     * account if it has to be created.
+     * 
+     * Throws a [ProgramError] for an invalid request, including when every vesting tranche still 
+     * holds locked tokens.
+     */
+    fn process_withdraw(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The transaction fee payer.
+        let payer_info = next_account_info(account_info_iter)?;
+        Check::signer(payer_info)?;
+
+        // The `mint authority` PDA account, or the employer's vault token account in vault mode.
+        let payout_info = next_account_info(account_info_iter)?;
+
+        // The `employer` PDA account.
+        let employer_info = next_account_info(account_info_iter)?;
+        Check::owner(&employer_info, program_id)?;
+        let mut employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+
+        // The `shift` account to withdraw from.
+        let shift_info = next_account_info(account_info_iter)?;
+        Check::owner(&shift_info, program_id)?;
+        let mut shift = BOQShift::try_from_slice(&shift_info.data.borrow())?;
+        Check::initialized(&shift, shift_info)?;
+
+        // The shift owner's wallet.
+        let owner_info = next_account_info(account_info_iter)?;
+        Check::pubkey(owner_info.key, &shift.owner)?;
+
+        // The `salary` token's mint account.
+        let token_mint_info = next_account_info(account_info_iter)?;
+        Check::pubkey(token_mint_info.key, &employer.token_mint)?;
+
+        // The `salary` token's receiver account.
+        let ata_info = next_account_info(account_info_iter)?;
+
+        // The token program (SPL Token or Token-2022).
+        let token_program_info = next_account_info(account_info_iter)?;
+        Check::token_program(token_program_info, token_mint_info)?;
+        Check::ata(ata_info, token_mint_info.key, &shift.owner, token_program_info.key)?;
+
+        // The system program.
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        // The associated token program.
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+
+        let claimable = shift.claimable;
+        Check::assert(claimable > 0, "Nothing to withdraw.")?;
+
+        // Lock the employer's share of the balance for vesting.
+        let amount = if employer.vesting_rate > 0 {
+            Check::signer(owner_info)?;
+
+            // The shift owner's `vesting` PDA account.
+            let vesting_info = next_account_info(account_info_iter)?;
+            let mut vesting = Self::_unpack_vesting(program_id, vesting_info, &shift.owner)?;
+
+            let slot = Clock::get()?.slot;
+            let locked = employer.locked_amount(claimable);
+            let vested = vesting.release(slot);
+            let is_locked = vesting.lock(locked, slot, employer.vesting_slots);
+            Check::assert(is_locked, "Vesting tranches full.")?;
+            vesting.serialize(&mut &mut vesting_info.data.borrow_mut()[..])?;
+
+            shift.total_locked += locked;
+            shift.total_vested += vested;
+            claimable - locked + vested
+        } else {
+            claimable
+        };
+
+        // Creates the owner's ATA if it does not exist.
+        invoke(
+            &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                payer_info.key, 
+                owner_info.key, 
+                token_mint_info.key, 
+                token_program_info.key,
+            ), 
+            &[
+                payer_info.clone(),
+                ata_info.clone(),
+                owner_info.clone(),
+                token_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        // Clear the balance before paying it out.
+        shift.claimable = 0;
+        shift.serialize(&mut &mut shift_info.data.borrow_mut()[..])?;
+
+        Self::_process_withdrawal(
+            program_id, 
+            account_info_iter, 
+            payout_info, 
+            employer_info, 
+            &mut employer, 
+            &shift, 
+            token_mint_info, 
+            ata_info, 
+            token_program_info, 
+            amount,
+        )
+    }
+
+    /**
+     * Creates a [BOQVesting] PDA account.
+     * 
+     * Throws a [ProgramError] if the account already exists.
+     */
+    fn process_create_vesting(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        bump: u8,
+    ) -> ProgramResult {
+        let owner_info = &accounts[0];
+        Self::_process_create_pda(
+            program_id, 
+            accounts, 
+            BOQVesting::MAX_SIZE, 
+            &[BOQSeed::VESTING.as_ref(), owner_info.key.as_ref(), &[bump]],
+            false,
+        )
+    }
+
+    /**
+     * Initializes a [BOQVesting] account.
+     * 
+     * This instruction does not check for signers and MUST be sent in the same transaction as 
+     * [BOQInstruction::CreateVesting] to prevent another account from taking control.
+     * 
+     * Throws a [ProgramError] if the account has already been initialized.
+     */
+    fn process_initialize_vesting(
+        _program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        bump: u8,
+        owner: &Pubkey,
+    ) -> ProgramResult {
+        
+        // The `vesting` PDA account to initialize.
+        let vesting_info = &accounts[0];
+        let vesting_data = &mut vesting_info.data.borrow_mut();
+        let vesting = BOQVesting::try_from_slice(&vesting_data)?;
+        Check::uninitialized(&vesting, vesting_info)?;
+
+        // Set account data.
+        BOQVesting::new(bump, *owner).serialize(&mut &mut vesting_data[..])?;
+
+        Ok(())
+    }
+
+    /**
+     * Pays out the vested rewards of a shift account's owner to the owner's associated token 
+     * account.
+     * 
+     * Takes the same accounts as [BOQInstruction::Withdraw], with the owner's vesting account 
+     * before the employer's treasury and split recipient token accounts.
+     * 
+     * Throws a [ProgramError] if no rewards have vested.
+     */
+    fn process_claim_vested(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The transaction fee payer.
+        let payer_info = next_account_info(account_info_iter)?;
+        Check::signer(payer_info)?;
+
+        // The `mint authority` PDA account, or the employer's vault token account in vault mode.
+        let payout_info = next_account_info(account_info_iter)?;
+
+        // The `employer` PDA account.
+        let employer_info = next_account_info(account_info_iter)?;
+        Check::owner(&employer_info, program_id)?;
+        let mut employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+
+        // The owner's `shift` account.
+        let shift_info = next_account_info(account_info_iter)?;
+        Check::owner(&shift_info, program_id)?;
+        let mut shift = BOQShift::try_from_slice(&shift_info.data.borrow())?;
+        Check::initialized(&shift, shift_info)?;
+
+        // The shift owner's wallet.
+        let owner_info = next_account_info(account_info_iter)?;
+        Check::pubkey(owner_info.key, &shift.owner)?;
+
+        // The `salary` token's mint account.
+        let token_mint_info = next_account_info(account_info_iter)?;
+        Check::pubkey(token_mint_info.key, &employer.token_mint)?;
+
+        // The `salary` token's receiver account.
+        let ata_info = next_account_info(account_info_iter)?;
+
+        // The token program (SPL Token or Token-2022).
+        let token_program_info = next_account_info(account_info_iter)?;
+        Check::token_program(token_program_info, token_mint_info)?;
+        Check::ata(ata_info, token_mint_info.key, &shift.owner, token_program_info.key)?;
+
+        // The system program.
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        // The associated token program.
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+
+        // The shift owner's `vesting` PDA account.
+        let vesting_info = next_account_info(account_info_iter)?;
+        let mut vesting = Self::_unpack_vesting(program_id, vesting_info, &shift.owner)?;
+
+        // Release the unlocked tokens.
+        let amount = vesting.release(Clock::get()?.slot);
+        Check::assert(amount > 0, "Nothing vested.")?;
+        vesting.serialize(&mut &mut vesting_info.data.borrow_mut()[..])?;
+        shift.total_vested += amount;
+        shift.serialize(&mut &mut shift_info.data.borrow_mut()[..])?;
+
+        // Creates the owner's ATA if it does not exist.
+        invoke(
+            &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                payer_info.key, 
+                owner_info.key, 
+                token_mint_info.key, 
+                token_program_info.key,
+            ), 
+            &[
+                payer_info.clone(),
+                ata_info.clone(),
+                owner_info.clone(),
+                token_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        Self::_process_withdrawal(
+            program_id, 
+            account_info_iter, 
+            payout_info, 
+            employer_info, 
+            &mut employer, 
+            &shift, 
+            token_mint_info, 
+            ata_info, 
+            token_program_info, 
+            amount,
+        )
+    }
+
+    /**
+     * Creates the PDA for [BOQRarityTable].
+     * 
+     * Throws a [ProgramError] if the account already exists.
+     */
+    fn process_create_rarity_table(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        bump: u8,
+    ) -> ProgramResult {
+        Self::_process_create_pda(
+            program_id, 
+            accounts, 
+            BOQRarityTable::MAX_SIZE, 
+            &[BOQSeed::RARITY_TABLE.as_ref(), &[bump]],
+            true,
+        )
+    }
+
+    /**
+     * Initializes the [BOQRarityTable] account.
+     * 
+     * This instruction does not check for signers and MUST be sent in the same transaction as 
+     * [BOQInstruction::CreateRarityTable] to prevent another account from taking control.
+     * 
+     * Throws a [ProgramError] if the account has already been initialized.
+     */
+    fn process_initialize_rarity_table(
+        _program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        bump: u8,
+        merkle_root: [u8; 32],
+    ) -> ProgramResult {
+        
+        // The `rarity table` PDA account to initialize.
+        let rarity_table_info = &accounts[0];
+        let rarity_table_data = &mut rarity_table_info.data.borrow_mut();
+        let rarity_table = BOQRarityTable::try_from_slice(&rarity_table_data)?;
+        Check::uninitialized(&rarity_table, rarity_table_info)?;
+
+        // Set account data.
+        BOQRarityTable::new(bump, merkle_root).serialize(&mut &mut rarity_table_data[..])?;
+
+        Ok(())
+    }
+
+    /**
+     * Sets `merkle_root` as the root of the rarity table's NFT mint to multiplier leaves.
+     * 
+     * Employees keep their assigned multiplier until it is assigned again.
+     * 
+     * `Must be signed by the shift program`.
+     */
+    fn process_set_rarity_table(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        merkle_root: [u8; 32],
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The `rarity table` PDA account.
+        let rarity_table_info = next_account_info(account_info_iter)?;
+        Check::owner(rarity_table_info, program_id)?;
+        let mut rarity_table = BOQRarityTable::try_from_slice(&rarity_table_info.data.borrow())?;
+        Check::initialized(&rarity_table, rarity_table_info)?;
+
+        // The shift program account.
+        let shift_program_info = next_account_info(account_info_iter)?;
+        Check::signer(shift_program_info)?;
+        Check::pubkey(shift_program_info.key, program_id)?;
+
+        rarity_table.merkle_root = merkle_root;
+        rarity_table.serialize(&mut &mut rarity_table_info.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /**
+     * Assigns the rarity `multiplier` (in basis points) of an employee, which applies to its base 
+     * rate ([BOQRewardMode::Employee] only).
+     * 
+     * This instruction does not check for signers as `proof` must prove that the rarity table 
+     * assigns `multiplier` to the employee's NFT.
+     * 
+     * Throws a [ProgramError] if the proof is invalid.
+     */
+    fn process_assign_rarity(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        multiplier: u16,
+        proof: &[[u8; 32]],
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The `rarity table` PDA account.
+        let rarity_table_info = next_account_info(account_info_iter)?;
+        Check::owner(rarity_table_info, program_id)?;
+        let rarity_table = BOQRarityTable::try_from_slice(&rarity_table_info.data.borrow())?;
+        Check::initialized(&rarity_table, rarity_table_info)?;
+
+        // The `employee` PDA account.
+        let employee_info = next_account_info(account_info_iter)?;
+        Check::owner(&employee_info, program_id)?;
+        let mut employee = BOQEmployee::try_from_slice(&employee_info.data.borrow())?;
+        Check::initialized(&employee, employee_info)?;
+
+        // Check that the rarity table assigns `multiplier` to the employee's NFT.
+        let leaf = BOQRarityTable::leaf(&employee.nft_mint, multiplier);
+        Check::merkle_proof(proof, &rarity_table.merkle_root, &leaf)?;
+
+        employee.rarity_multiplier = multiplier;
+        employee.serialize(&mut &mut employee_info.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /**
+     * Creates the PDA for [BOQSchedule].
+     * 
+     * Throws a [ProgramError] if the account already exists.
+     */
+    fn process_create_schedule(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        bump: u8,
+    ) -> ProgramResult {
+        Self::_process_create_pda(
+            program_id, 
+            accounts, 
+            BOQSchedule::MAX_SIZE, 
+            &[BOQSeed::SCHEDULE.as_ref(), &[bump]],
+            true,
+        )
+    }
+
+    /**
+     * Initializes the [BOQSchedule] account and enables it on the employer. Once enabled, the 
+     * schedule account must follow the shift account in [BOQInstruction::Shift] and 
+     * [BOQInstruction::Unstake], and the NFT token account in [BOQInstruction::Checkpoint].
+     * 
+     * This instruction does not check for signers and MUST be sent in the same transaction as 
+     * [BOQInstruction::CreateSchedule] to prevent another account from taking control.
+     * 
+     * Throws a [ProgramError] if the account has already been initialized.
+     */
+    fn process_initialize_schedule(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        bump: u8,
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+        
+        // The `schedule` PDA account to initialize.
+        let schedule_info = next_account_info(account_info_iter)?;
+        let schedule_data = &mut schedule_info.data.borrow_mut();
+        let schedule = BOQSchedule::try_from_slice(&schedule_data)?;
+        Check::uninitialized(&schedule, schedule_info)?;
+
+        // The `employer` PDA account.
+        let employer_info = next_account_info(account_info_iter)?;
+        Check::owner(&employer_info, program_id)?;
+        let mut employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+
+        // Set account data.
+        BOQSchedule::new(bump).serialize(&mut &mut schedule_data[..])?;
+        employer.has_schedule = true;
+        employer.serialize(&mut &mut employer_info.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /**
+     * Schedules a bonus window from `start_slot` to `end_slot` in which `multiplier` (in basis 
+     * points) applies to the base rate of all employees ([BOQRewardMode::Employee] only).
+     * 
+     * Windows can only be scheduled from the current slot onwards, and a window can only be 
+     * replaced before it starts or once no employee can be paid for its slots anymore, so that 
+     * the multipliers of slots already worked never change.
+     * 
+     * `Must be signed by the shift program`.
+     * 
+     * Throws a [ProgramError] if `index` or the slot range is invalid, or the window at `index` 
+     * still applies to unsettled slots.
+     */
+    fn process_set_bonus_window(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        index: u8,
+        start_slot: Slot,
+        end_slot: Slot,
+        multiplier: u16,
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The `schedule` PDA account.
+        let schedule_info = next_account_info(account_info_iter)?;
+        Check::owner(schedule_info, program_id)?;
+        let mut schedule = BOQSchedule::try_from_slice(&schedule_info.data.borrow())?;
+        Check::initialized(&schedule, schedule_info)?;
+
+        // The shift program account.
+        let shift_program_info = next_account_info(account_info_iter)?;
+        Check::signer(shift_program_info)?;
+        Check::pubkey(shift_program_info.key, program_id)?;
+
+        // The `employer` PDA account.
+        let employer_info = next_account_info(account_info_iter)?;
+        Check::owner(&employer_info, program_id)?;
+        let employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+
+        let index = usize::from(index);
+        Check::assert(index < BOQSchedule::MAX_WINDOWS, "Invalid window index.")?;
+        let slot = Clock::get()?.slot;
+        Check::assert(start_slot >= slot && start_slot <= end_slot, "Invalid window slots.")?;
+
+        // Unsettled slots are at most one shift old.
+        let window = schedule.windows[index];
+        let is_settled = window.end_slot.saturating_add(employer.slots_per_shift) <= slot;
+        let is_replaceable = window.start_slot > slot || is_settled;
+        Check::assert(is_replaceable, "Window in use.")?;
+
+        schedule.windows[index] = BOQBonusWindow { start_slot, end_slot, multiplier };
+        schedule.serialize(&mut &mut schedule_info.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /**
+     * Unpacks the next account as the [BOQSchedule] account if the employer has one.
+     */
+    fn _next_schedule<'a, 'b: 'a>(
+        program_id: &Pubkey,
+        account_info_iter: &mut Iter<'a, AccountInfo<'b>>,
+        employer: &BOQEmployer,
+    ) -> Result<Option<BOQSchedule>, ProgramError> {
+        if !employer.has_schedule {
+            return Ok(None);
+        }
+
+        // The `schedule` PDA account.
+        let schedule_info = next_account_info(account_info_iter)?;
+        Check::owner(schedule_info, program_id)?;
+        let schedule = BOQSchedule::try_from_slice(&schedule_info.data.borrow())?;
+        Check::initialized(&schedule, schedule_info)?;
+        Ok(Some(schedule))
+    }
+
+    /**
+     * Unpacks the [BOQVesting] PDA account of `owner`.
+     * 
+     * Throws a [ProgramError] if `vesting_info` is not the initialized vesting account of `owner`.
+     */
+    fn _unpack_vesting(
+        program_id: &Pubkey,
+        vesting_info: &AccountInfo,
+        owner: &Pubkey,
+    ) -> Result<BOQVesting, ProgramError> {
+        Check::owner(vesting_info, program_id)?;
+        let vesting = BOQVesting::try_from_slice(&vesting_info.data.borrow())?;
+        Check::initialized(&vesting, vesting_info)?;
+        Check::pubkey(&vesting.owner, owner)?;
+        let vesting_key = Pubkey::create_program_address(
+            &[BOQSeed::VESTING.as_ref(), owner.as_ref(), &[vesting.bump]], 
+            program_id,
+        )?;
+        Check::pubkey(vesting_info.key, &vesting_key)?;
+        Ok(vesting)
+    }
+
+    /**
+     * Unpacks the next account as the employee's [BOQRental] account if the employee has one.
+     */
+    fn _next_rental<'a, 'b: 'a>(
+        program_id: &Pubkey,
+        account_info_iter: &mut Iter<'a, AccountInfo<'b>>,
+        employee_info: &AccountInfo,
+        employee: &BOQEmployee,
+    ) -> Result<Option<BOQRental>, ProgramError> {
+        if !employee.has_rental {
+            return Ok(None);
+        }
+
+        // The employee's `rental` PDA account.
+        let rental_info = next_account_info(account_info_iter)?;
+        let rental = Self::_unpack_rental(program_id, rental_info, employee_info.key)?;
+        Ok(Some(rental))
+    }
+
+    /**
+     * Unpacks the [BOQRental] PDA account of `employee`.
+     * 
+     * Throws a [ProgramError] if `rental_info` is not the initialized rental account of `employee`.
+     */
+    fn _unpack_rental(
+        program_id: &Pubkey,
+        rental_info: &AccountInfo,
+        employee: &Pubkey,
+    ) -> Result<BOQRental, ProgramError> {
+        Check::owner(rental_info, program_id)?;
+        let rental = BOQRental::try_from_slice(&rental_info.data.borrow())?;
+        Check::initialized(&rental, rental_info)?;
+        Check::pubkey(&rental.employee, employee)?;
+        let rental_seeds = &[BOQSeed::RENTAL.as_ref(), employee.as_ref(), &[rental.bump]];
+        Check::pda(program_id, rental_info, rental_seeds)?;
+        Ok(rental)
+    }
+
+    /**
+     * Returns the wallet that an employee works for at `slot`: the operator of an active rental 
+     * agreement set by the NFT's `holder`, or else the holder.
+     */
+    fn _worker(
+        rental: Option<&BOQRental>,
+        holder: Option<Pubkey>,
+        slot: Slot,
+    ) -> Option<Pubkey> {
+        match rental {
+            Some(rental) if rental.is_active(slot) && holder == Some(rental.owner) => {
+                Some(rental.operator)
+            },
+            _ => holder,
+        }
+    }
+
+    /**
+     * Burns the employer's `boost_cost` in `salary` tokens from the shift owner's token account to 
+     * apply the employer's `boost_multiplier` to an employee's base rate for `boost_slots` slots 
+     * ([BOQRewardMode::Employee] only). Boosting an employee that is already boosted extends the 
+     * boost.
+     * 
+     * `Must be signed by the shift owner`.
+     * 
+     * Throws a [ProgramError] if the employer does not offer paid boosts in 
+     * [BOQRewardMode::Employee] or the employee is not working for the shift owner.
+     */
+    fn process_boost(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The shift owner.
+        let owner_info = next_account_info(account_info_iter)?;
+        Check::signer(owner_info)?;
+
+        // The shift owner's `salary` token account to burn from.
+        let source_info = next_account_info(account_info_iter)?;
+
+        // The `employer` PDA account.
+        let employer_info = next_account_info(account_info_iter)?;
+        Check::owner(&employer_info, program_id)?;
+        let employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+        let is_offered = employer.reward_mode == BOQRewardMode::Employee
+            && employer.boost_cost > 0
+            && employer.boost_slots > 0 
+            && employer.boost_multiplier > 0;
+        Check::assert(is_offered, "Boost not available.")?;
+
+        // The `employee` PDA account.
+        let employee_info = next_account_info(account_info_iter)?;
+        Check::owner(&employee_info, program_id)?;
+        let mut employee = BOQEmployee::try_from_slice(&employee_info.data.borrow())?;
+        Check::initialized(&employee, employee_info)?;
+
+        // The NFT token account (the employee's escrow account in custodial mode).
+        let nft_token_info = next_account_info(account_info_iter)?;
+        let nft_token = Self::_unpack_token_account(nft_token_info)?;
+        Check::pubkey(&nft_token.mint, &employee.nft_mint)?;
+        let holder = Self::_holder(&employer, employee_info, &employee, &nft_token);
+        Check::assert(holder == Some(*owner_info.key), "Invalid NFT holder.")?;
+
+        // The `salary` token's mint account.
+        let token_mint_info = next_account_info(account_info_iter)?;
+        Check::pubkey(token_mint_info.key, &employer.token_mint)?;
+        let token_mint = Self::_unpack_mint(token_mint_info)?;
+
+        // The token program (SPL Token or Token-2022).
+        let token_program_info = next_account_info(account_info_iter)?;
+        Check::token_program(token_program_info, token_mint_info)?;
+
+        // Boost the employee from the current slot.
+        employee.boost(&employer, Clock::get()?.slot);
+        employee.serialize(&mut &mut employee_info.data.borrow_mut()[..])?;
+
+        invoke(
+            &spl_token_2022::instruction::burn_checked(
+                token_program_info.key, 
+                source_info.key, 
+                token_mint_info.key, 
+                owner_info.key, 
+                &[], 
+                employer.boost_cost, 
+                token_mint.decimals,
+            )?,
+            &[
+                source_info.clone(),
+                token_mint_info.clone(),
+                owner_info.clone(),
+                token_program_info.clone(),
+            ],
+        )
+    }
+
+    /**
+     * Sets `splits` as the split table of a shift account. Each recipient with a non-zero share is 
+     * paid its share of every withdrawal from the shift account after fees, and the owner is paid 
+     * the remainder. Shares of zero leave an entry unused.
+     * 
+     * `Must be signed by the shift owner`.
+     * 
+     * Throws a [ProgramError] if the shares exceed [MAX_BASIS_POINTS].
+     */
+    fn process_set_splits(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        splits: [BOQSplit; BOQShift::MAX_SPLITS],
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The shift owner.
+        let owner_info = next_account_info(account_info_iter)?;
+        Check::signer(owner_info)?;
+
+        // The owner's `shift` account.
+        let shift_info = next_account_info(account_info_iter)?;
+        Check::owner(&shift_info, program_id)?;
+        let mut shift = BOQShift::try_from_slice(&shift_info.data.borrow())?;
+        Check::initialized(&shift, shift_info)?;
+        Check::pubkey(owner_info.key, &shift.owner)?;
+
+        let total_share: u64 = splits.iter().map(|split| u64::from(split.share)).sum();
+        Check::assert(total_share <= MAX_BASIS_POINTS, "Invalid split shares.")?;
+        let has_recipients = splits.iter()
+            .all(|split| split.share == 0 || split.recipient != Pubkey::default());
+        Check::assert(has_recipients, "Missing split recipient.")?;
+
+        shift.splits = splits;
+        shift.serialize(&mut &mut shift_info.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /**
+     * Creates a [BOQRental] PDA account for the employee that follows the system program.
+     * 
+     * Throws a [ProgramError] if the account already exists.
+     */
+    fn process_create_rental(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        bump: u8,
+    ) -> ProgramResult {
+        let employee_info = &accounts[4];
+        Self::_process_create_pda(
+            program_id, 
+            accounts, 
+            BOQRental::MAX_SIZE, 
+            &[BOQSeed::RENTAL.as_ref(), employee_info.key.as_ref(), &[bump]],
+            false,
+        )
+    }
+
+    /**
+     * Initializes a [BOQRental] account for its employee. The account is only enabled on the 
+     * employee once the NFT holder sets an agreement (see [Processor::process_set_rental]).
+     * 
+     * This instruction does not check for signers as the account is bound to its employee and 
+     * changes nothing until the NFT holder sets an agreement.
+     * 
+     * Throws a [ProgramError] if the account has already been initialized.
+     */
+    fn process_initialize_rental(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        bump: u8,
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+        
+        // The `rental` PDA account to initialize.
+        let rental_info = next_account_info(account_info_iter)?;
+        let rental_data = &mut rental_info.data.borrow_mut();
+        let rental = BOQRental::try_from_slice(&rental_data)?;
+        Check::uninitialized(&rental, rental_info)?;
+
+        // The `employee` PDA account.
+        let employee_info = next_account_info(account_info_iter)?;
+        Check::owner(&employee_info, program_id)?;
+        let employee = BOQEmployee::try_from_slice(&employee_info.data.borrow())?;
+        Check::initialized(&employee, employee_info)?;
+        let rental_seeds = &[BOQSeed::RENTAL.as_ref(), employee_info.key.as_ref(), &[bump]];
+        Check::pda(program_id, rental_info, rental_seeds)?;
+
+        // Set account data.
+        BOQRental::new(bump, *employee_info.key).serialize(&mut &mut rental_data[..])?;
+
+        Ok(())
+    }
+
+    /**
+     * Lends an employee to `operator` from `start_slot` to `end_slot`. While the agreement is 
+     * active, the employee works for the operator's shift account instead of the NFT holder's, 
+     * and `owner_share` (in basis points) of the operator's wage is credited to the holder's shift 
+     * account. The agreement expires at `end_slot`, after which the employee works for the holder 
+     * again.
+     * 
+     * The agreement only applies while the NFT stays with the holder that set it, and a new holder 
+     * can replace an agreement set by a previous holder before it expires.
+     * 
+     * The first agreement enables the rental account on the employee. From then on, the rental 
+     * account must follow the employee's accounts in [BOQInstruction::Shift], the schedule 
+     * account in [BOQInstruction::Checkpoint] and the NFT's token program in 
+     * [BOQInstruction::Unstake].
+     * 
+     * `Must be signed by the NFT holder`.
+     * 
+     * Throws a [ProgramError] if the holder's previous agreement has not expired yet, or if the 
+     * previous operator's wage has not been settled.
+     */
+    fn process_set_rental(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        operator: &Pubkey,
+        start_slot: Slot,
+        end_slot: Slot,
+        owner_share: u16,
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The NFT holder.
+        let owner_info = next_account_info(account_info_iter)?;
+        Check::signer(owner_info)?;
+
+        // The `employer` PDA account.
+        let employer_info = next_account_info(account_info_iter)?;
+        Check::owner(&employer_info, program_id)?;
+        let employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+
+        // The `employee` PDA account.
+        let employee_info = next_account_info(account_info_iter)?;
+        Check::owner(&employee_info, program_id)?;
+        let mut employee = BOQEmployee::try_from_slice(&employee_info.data.borrow())?;
+        Check::initialized(&employee, employee_info)?;
+
+        // The NFT token account (the employee's escrow account in custodial mode).
+        let nft_token_info = next_account_info(account_info_iter)?;
+        let nft_token = Self::_unpack_token_account(nft_token_info)?;
+        Check::pubkey(&nft_token.mint, &employee.nft_mint)?;
+        let holder = Self::_holder(&employer, employee_info, &employee, &nft_token);
+        Check::assert(holder == Some(*owner_info.key), "Invalid NFT holder.")?;
+
+        // The employee's `rental` PDA account.
+        let rental_info = next_account_info(account_info_iter)?;
+        let mut rental = Self::_unpack_rental(program_id, rental_info, employee_info.key)?;
+
+        // An agreement set by a previous NFT holder no longer applies and can be replaced.
+        let slot = Clock::get()?.slot;
+        let is_replaceable = slot >= rental.end_slot || rental.owner.ne(owner_info.key);
+        Check::assert(is_replaceable, "Rental not expired.")?;
+
+        // The previous operator's wage must be settled first (see [Processor::process_checkpoint]) 
+        // so that the previous owner's share is still withheld from it.
+        let is_settled = rental.operator == Pubkey::default() 
+            || employee.holder.ne(&rental.operator);
+        Check::assert(is_settled, "Rental not settled.")?;
+        Check::assert(start_slot < end_slot && end_slot > slot, "Invalid rental slots.")?;
+        Check::assert(u64::from(owner_share) <= MAX_BASIS_POINTS, "Invalid owner share.")?;
+        Check::assert(operator.ne(owner_info.key), "Invalid operator.")?;
+
+        rental.owner = *owner_info.key;
+        rental.operator = *operator;
+        rental.start_slot = start_slot;
+        rental.end_slot = end_slot;
+        rental.owner_share = owner_share;
+        rental.serialize(&mut &mut rental_info.data.borrow_mut()[..])?;
+
+        // Enable the rental account on the employee.
+        if !employee.has_rental {
+            employee.has_rental = true;
+            employee.serialize(&mut &mut employee_info.data.borrow_mut()[..])?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Migrates an employer, employee or shift account created before account layouts were 
+     * versioned to the current layout. The account keeps its state, the fields added since take 
+     * their defaults, and the payer tops up the account's rent for its new size.
+     * 
+     * A shift account created with a non-canonical bump is moved to its canonical address, which 
+     * must follow the system program together with the shift program. The legacy account is 
+     * closed and its rent refunded to the payer.
+     * 
+     * This instruction only checks the payer's signature, as the migrated state does not depend 
+     * on the caller.
+     * 
+     * Throws a [ProgramError] if the account is not a legacy employer, employee or shift account.
+     */
+    fn process_migrate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The transaction fee payer.
+        let payer_info = next_account_info(account_info_iter)?;
+        Check::signer(payer_info)?;
+
+        // The legacy `employer`, `employee` or `shift` PDA account.
+        let legacy_info = next_account_info(account_info_iter)?;
+        Check::owner(&legacy_info, program_id)?;
+
+        // The system program.
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        // Decode the legacy layout, which is only identified by its size.
+        let data = {
+            let legacy_data = &legacy_info.data.borrow();
+            match (BOQAccountType::deserialize(&mut &legacy_data[..])?, legacy_data.len()) {
+                (BOQAccountType::Employer, BOQEmployer::LEGACY_SIZE) => {
+                    Some(BOQEmployer::from_legacy(legacy_data)?.try_to_vec()?)
+                },
+                (BOQAccountType::Employee, BOQEmployee::LEGACY_SIZE) => {
+                    Some(BOQEmployee::from_legacy(legacy_data)?.try_to_vec()?)
+                },
+                (BOQAccountType::Shift, BOQShift::LEGACY_SIZE) => {
+                    Some(BOQShift::from_legacy(legacy_data)?.try_to_vec()?)
+                },
+                _ => None,
+            }
+        };
+        Check::assert(data.is_some(), "Invalid legacy account.")?;
+        let data = data.unwrap();
+
+        // Move a shift account created with a non-canonical bump to its canonical address, so that 
+        // an empty canonical address proves that its owner has no shift account (see 
+        // [Processor::_checkpoint]).
+        if BOQAccountType::deserialize(&mut &data[..])? == BOQAccountType::Shift {
+            let mut shift = BOQShift::try_from_slice(&data)?;
+            let (shift_key, shift_bump) = Pubkey::find_program_address(
+                &[BOQSeed::SHIFT.as_ref(), shift.owner.as_ref()], 
+                program_id,
+            );
+            if shift.bump != shift_bump {
+
+                // The shift owner's `shift` PDA account at its canonical address.
+                let shift_info = next_account_info(account_info_iter)?;
+                Check::pubkey(shift_info.key, &shift_key)?;
+
+                // The shift program.
+                let shift_program_info = next_account_info(account_info_iter)?;
+                Check::pubkey(shift_program_info.key, program_id)?;
+
+                Self::_process_create_pda(
+                    program_id, 
+                    &[
+                        payer_info.clone(),
+                        shift_info.clone(),
+                        shift_program_info.clone(),
+                        system_program_info.clone(),
+                    ], 
+                    BOQShift::MAX_SIZE, 
+                    &[BOQSeed::SHIFT.as_ref(), shift.owner.as_ref(), &[shift_bump]],
+                    false,
+                )?;
+                shift.bump = shift_bump;
+                shift.serialize(&mut &mut shift_info.data.borrow_mut()[..])?;
+
+                // Close the legacy account, refunding its rent to the payer.
+                let lamports = legacy_info.lamports();
+                **legacy_info.lamports.borrow_mut() = 0;
+                **payer_info.lamports.borrow_mut() += lamports;
+                legacy_info.realloc(0, false)?;
+
+                return Ok(());
+            }
+        }
+
+        // Top up the rent for the new size.
+        let rent = Rent::get()?;
+        let lamports = rent.minimum_balance(data.len()).saturating_sub(legacy_info.lamports());
+        if lamports > 0 {
+            invoke(
+                &system_instruction::transfer(payer_info.key, legacy_info.key, lamports),
+                &[
+                    payer_info.clone(),
+                    legacy_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+
+        legacy_info.realloc(data.len(), false)?;
+        legacy_info.data.borrow_mut().copy_from_slice(&data);
+
+        Ok(())
+    }
+
+    /**
+     * Credits the wage accrued by an employee to its previous holder if the NFT has changed hands, 
+     * and starts accruing the wage for the current holder from the current slot.
+     * 
+     * The previous holder's shift account must follow the NFT token account when the current 
+     * holder differs from the employee's recorded holder. If the previous holder has not created 
+     * a shift account, the pending wage is forfeited.
+     * 
+     * The operator of an active rental agreement counts as the current holder, and an operator 
+     * whose agreement has expired is only paid up to the agreement's end slot. When an operator's 
+     * wage is settled, the rental owner's shift account must follow the operator's shift account 
+     * to receive the owner's share.
+     * 
+     * This instruction does not check for signers so that anyone can settle a sale.
+     * 
+     * Throws a [ProgramError] if the NFT token account does not hold a working NFT.
+     */
+    fn process_checkpoint(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The `employer` PDA account.
+        let employer_info = next_account_info(account_info_iter)?;
+        Check::owner(&employer_info, program_id)?;
+        let mut employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+
+        // The `employee` PDA account.
+        let employee_info = next_account_info(account_info_iter)?;
+        Check::owner(&employee_info, program_id)?;
+        let mut employee = BOQEmployee::try_from_slice(&employee_info.data.borrow())?;
+        Check::initialized(&employee, employee_info)?;
+
+        // The NFT token account (the employee's escrow account in custodial mode).
+        let nft_token_info = next_account_info(account_info_iter)?;
+        let nft_token = Self::_unpack_token_account(nft_token_info)?;
+        Check::pubkey(&nft_token.mint, &employee.nft_mint)?;
+
+        // The bonus window schedule.
+        let schedule = Self::_next_schedule(program_id, account_info_iter, &employer)?;
+
+        // The employee's rental agreement.
+        let rental = Self::_next_rental(program_id, account_info_iter, employee_info, &employee)?;
+
+        // Settle the wage up to the current slot, within the employer's running period.
+        let slot = min(Clock::get()?.slot, employer.end_slot);
+
+        // The wallet the employee currently works for.
+        let holder = Self::_holder(&employer, employee_info, &employee, &nft_token);
+        let holder = Self::_worker(rental.as_ref(), holder, slot);
+        Check::assert(holder.is_some(), "Invalid NFT holder.")?;
+        let holder = holder.unwrap();
+
+        if employer.is_active && slot >= employer.start_slot {
+            employer.update_pool(slot);
+            let owner_amount = Self::_checkpoint(
+                program_id, 
+                account_info_iter, 
+                &mut employer, 
+                schedule.as_ref(), 
+                rental.as_ref(), 
+                &mut employee, 
+                &holder, 
+                slot,
+            )?;
+            if let (Some(rental), Some(owner_amount)) = (rental.as_ref(), owner_amount) {
+                Self::_credit_rental_owner(
+                    program_id, 
+                    account_info_iter, 
+                    rental, 
+                    None, 
+                    owner_amount,
+                )?;
+            }
+        } else {
+            employee.holder = holder;
+        }
+
+        employee.serialize(&mut &mut employee_info.data.borrow_mut()[..])?;
+        employer.serialize(&mut &mut employer_info.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /**
+     * Stakes an NFT held by the staker.
+     * 
+     * In [BOQStakingMode::Custodial] the NFT is transferred into the employee's escrow account. In 
+     * [BOQStakingMode::Freeze] the employee PDA is approved as the delegate of the staker's NFT 
+     * token account and freezes it, so the NFT stays in the staker's wallet but cannot move. The 
+     * NFT token account must be the staker's associated token account.
+     * 
+     * Programmable NFTs are transferred, delegated and locked through the token metadata program, 
+     * which validates the NFT's rule set.
+     * 
+     * Rewards only accrue while the NFT is staked, starting from the current slot.
+     * 
+     * `Must be signed by the staker`.
+     * 
+     * Throws a [ProgramError] if the employer does not support staking or the employee is already 
+     * staked.
+     */
+    fn process_stake(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The NFT holder (transaction fee payer).
+        let staker_info = next_account_info(account_info_iter)?;
+        Check::signer(staker_info)?;
+
+        // The staker's NFT token account.
+        let nft_token_info = next_account_info(account_info_iter)?;
+
+        // The `employer` PDA account.
+        let employer_info = next_account_info(account_info_iter)?;
+        Check::owner(&employer_info, program_id)?;
+        let mut employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+        let staking_message = "Staking not available.";
+        Check::assert(employer.staking_mode != BOQStakingMode::None, staking_message)?;
+
+        // The `employee` PDA account.
+        let employee_info = next_account_info(account_info_iter)?;
+        Check::owner(&employee_info, program_id)?;
+        let mut employee = BOQEmployee::try_from_slice(&employee_info.data.borrow())?;
+        Check::initialized(&employee, employee_info)?;
+        Check::assert(!employee.is_staked, "Employee already staked.")?;
+
+        // The NFT's mint account.
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        Check::pubkey(nft_mint_info.key, &employee.nft_mint)?;
+
+        // The NFT's metadata account.
+        let nft_metadata_info = next_account_info(account_info_iter)?;
+        Check::owner(nft_metadata_info, &mpl_token_metadata::ID)?;
+        let nft_metadata = Metadata::from_account_info(nft_metadata_info)?;
+        Check::pubkey(&nft_metadata.mint, nft_mint_info.key)?;
+
+        // The NFT's token program (SPL Token or Token-2022).
+        let nft_token_program_info = next_account_info(account_info_iter)?;
+        Check::token_program(nft_token_program_info, nft_mint_info)?;
+
+        // Check that a frozen NFT can be thawed by [BOQInstruction::Unstake], which only accepts 
+        // the staker's associated token account.
+        if employer.staking_mode == BOQStakingMode::Freeze {
+            let nft_token_program_id = nft_token_program_info.key;
+            Check::ata(nft_token_info, nft_mint_info.key, staker_info.key, nft_token_program_id)?;
+        }
+
+        // Start accruing rewards from the current slot.
+        let slot = Clock::get()?.slot;
+        employer.update_pool(slot);
+        employer.clock_in(&mut employee, slot);
+        employee.is_staked = true;
+        employee.staker = *staker_info.key;
+        employee.holder = *staker_info.key;
+        employee.serialize(&mut &mut employee_info.data.borrow_mut()[..])?;
+        employer.serialize(&mut &mut employer_info.data.borrow_mut()[..])?;
+
+        let nft_infos = [
+            staker_info.clone(),
+            nft_token_info.clone(),
+            employee_info.clone(),
+            nft_mint_info.clone(),
+            nft_metadata_info.clone(),
+            nft_token_program_info.clone(),
+        ];
+
+        match employer.staking_mode {
+            BOQStakingMode::None => {
+                Ok(())
+            },
+            BOQStakingMode::Custodial => {
+                Self::_process_escrow_nft(
+                    account_info_iter, 
+                    &nft_infos, 
+                    &employee, 
+                    &nft_metadata,
+                )
+            },
+            BOQStakingMode::Freeze => {
+                Self::_process_freeze_nft(
+                    account_info_iter, 
+                    &nft_infos, 
+                    &employee, 
+                    &nft_metadata,
+                )
+            },
+        }
+    }
+
+    /**
+     * Credits the employee's available wage to its staker's claimable balance and unstakes the NFT.
+     * 
+     * In [BOQStakingMode::Custodial] the escrowed NFT is returned to the staker's associated token 
+     * account. In [BOQStakingMode::Freeze] the staker's NFT token account is thawed and the 
+     * employee PDA's delegation is revoked.
+     * 
+     * If the employee has a rental account, it follows the NFT's token program. An employee 
+     * cannot be unstaked while lent to an operator. If the wage of an operator whose agreement 
+     * has expired has not been settled yet, the operator's shift account follows, and the 
+     * operator is paid first (see [Processor::process_checkpoint]).
+     * 
+     * `Must be signed by the staker`.
+     * 
+     * Throws a [ProgramError] if the employee is not staked by the signer, is committed to a 
+     * lock-up or is lent to an operator.
+     */
+    fn process_unstake(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The `employer` PDA account.
+        let employer_info = next_account_info(account_info_iter)?;
+        Check::owner(&employer_info, program_id)?;
+        let mut employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+
+        // The staker's `shift` account.
+        let shift_info = next_account_info(account_info_iter)?;
+        Check::owner(&shift_info, program_id)?;
+        let mut shift = BOQShift::try_from_slice(&shift_info.data.borrow())?;
+        Check::initialized(&shift, shift_info)?;
+
+        // The bonus window schedule.
+        let schedule = Self::_next_schedule(program_id, account_info_iter, &employer)?;
+
+        // The NFT staker (transaction fee payer).
+        let staker_info = next_account_info(account_info_iter)?;
+        Check::signer(staker_info)?;
+        Check::pubkey(&shift.owner, staker_info.key)?;
+
+        // The staker's NFT token account.
+        let nft_token_info = next_account_info(account_info_iter)?;
+
+        // The `employee` PDA account.
+        let employee_info = next_account_info(account_info_iter)?;
+        Check::owner(&employee_info, program_id)?;
+        let mut employee = BOQEmployee::try_from_slice(&employee_info.data.borrow())?;
+        Check::initialized(&employee, employee_info)?;
+        Check::assert(employee.is_staked, "Employee not staked.")?;
+        Check::pubkey(&employee.staker, staker_info.key)?;
+        Check::assert(!employee.is_locked(Clock::get()?.slot), "Employee locked.")?;
+
+        // The NFT's mint account.
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        Check::pubkey(nft_mint_info.key, &employee.nft_mint)?;
+
+        // The NFT's metadata account.
+        let nft_metadata_info = next_account_info(account_info_iter)?;
+        Check::owner(nft_metadata_info, &mpl_token_metadata::ID)?;
+        let nft_metadata = Metadata::from_account_info(nft_metadata_info)?;
+        Check::pubkey(&nft_metadata.mint, nft_mint_info.key)?;
+
+        // The NFT's token program (SPL Token or Token-2022).
+        let nft_token_program_info = next_account_info(account_info_iter)?;
+        Check::token_program(nft_token_program_info, nft_mint_info)?;
+        let nft_token_program_id = nft_token_program_info.key;
+        Check::ata(nft_token_info, nft_mint_info.key, staker_info.key, nft_token_program_id)?;
+
+        // The employee's rental agreement.
+        let rental = Self::_next_rental(program_id, account_info_iter, employee_info, &employee)?;
+
+        // Check that the employee is not lent to an operator.
+        let slot = min(Clock::get()?.slot, employer.end_slot);
+        let worker = Self::_worker(rental.as_ref(), Some(*staker_info.key), slot);
+        Check::assert(worker == Some(*staker_info.key), "Employee rented.")?;
+
+        // Settle the rewards accrued while staked, paying an operator whose agreement has 
+        // expired first.
+        if employer.is_active && slot >= employer.start_slot {
+            employer.update_pool(slot);
+            let owner_amount = Self::_checkpoint(
+                program_id, 
+                account_info_iter, 
+                &mut employer, 
+                schedule.as_ref(), 
+                rental.as_ref(), 
+                &mut employee, 
+                staker_info.key, 
+                slot,
+            )?;
+            if let (Some(rental), Some(owner_amount)) = (rental.as_ref(), owner_amount) {
+                Self::_credit_rental_owner(
+                    program_id, 
+                    account_info_iter, 
+                    rental, 
+                    Some(&mut shift), 
+                    owner_amount,
+                )?;
+            }
+            shift.claimable += Self::_work_shift(
+                &mut employer, 
+                schedule.as_ref(), 
+                &mut employee, 
+                &mut shift, 
+                slot,
+            );
+        } else {
+            employee.holder = *staker_info.key;
+        }
+        employee.is_staked = false;
+        employee.staker = Pubkey::default();
+        employee.serialize(&mut &mut employee_info.data.borrow_mut()[..])?;
+        shift.serialize(&mut &mut shift_info.data.borrow_mut()[..])?;
+        employer.serialize(&mut &mut employer_info.data.borrow_mut()[..])?;
+
+        let nft_infos = [
+            staker_info.clone(),
+            nft_token_info.clone(),
+            employee_info.clone(),
+            nft_mint_info.clone(),
+            nft_metadata_info.clone(),
+            nft_token_program_info.clone(),
+        ];
+
+        match employer.staking_mode {
+            BOQStakingMode::None => {
+                Ok(())
+            },
+            BOQStakingMode::Custodial => {
+                Self::_process_release_nft(
+                    account_info_iter, 
+                    &nft_infos, 
+                    &employee, 
+                    &nft_metadata,
+                )
+            },
+            BOQStakingMode::Freeze => {
+                Self::_process_thaw_nft(
+                    account_info_iter, 
+                    &nft_infos, 
+                    &employee, 
+                    &nft_metadata,
+                )
+            },
+        }
+    }
+
+    /**
+     * Commits a staked employee to the employer's lock-up `tier`. The employee cannot be unstaked 
+     * until the lock-up ends, and the tier's multiplier applies to its base rate in the meantime 
+     * ([BOQRewardMode::Employee] only).
+     * 
+     * `Must be signed by the staker`.
+     * 
+     * Throws a [ProgramError] if the tier is not offered, or the employee is not staked by the 
+     * signer or is already locked.
+     */
+    fn process_lock(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        tier: u8,
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The NFT staker.
+        let staker_info = next_account_info(account_info_iter)?;
+        Check::signer(staker_info)?;
+
+        // The `employer` PDA account.
+        let employer_info = next_account_info(account_info_iter)?;
+        Check::owner(&employer_info, program_id)?;
+        let employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+
+        // The `employee` PDA account.
+        let employee_info = next_account_info(account_info_iter)?;
+        Check::owner(&employee_info, program_id)?;
+        let mut employee = BOQEmployee::try_from_slice(&employee_info.data.borrow())?;
+        Check::initialized(&employee, employee_info)?;
+        Check::assert(employee.is_staked, "Employee not staked.")?;
+        Check::pubkey(&employee.staker, staker_info.key)?;
+
+        // The lock-up tier.
+        let tier = usize::from(tier);
+        let is_tier = tier < BOQEmployer::MAX_LOCK_TIERS && employer.lock_tiers[tier].shifts > 0;
+        Check::assert(is_tier, "Invalid lock tier.")?;
+        let lock_tier = employer.lock_tiers[tier];
+
+        // Commit the employee from the current slot.
+        let slot = Clock::get()?.slot;
+        Check::assert(!employee.is_locked(slot), "Employee locked.")?;
+        employee.lock_start_slot = slot;
+        employee.lock_end_slot = slot + (u64::from(lock_tier.shifts) * employer.slots_per_shift);
+        employee.lock_multiplier = lock_tier.multiplier;
+        employee.serialize(&mut &mut employee_info.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /**
+     * Transfers the staker's NFT into the employee's escrow account, creating the escrow account 
+     * if it does not exist.
+     * 
+     * `nft_infos` holds the staker, NFT token, employee, NFT mint, NFT metadata and NFT token 
+     * program accounts.
+     */
+    fn _process_escrow_nft<'a, 'b: 'a>(
+        account_info_iter: &mut Iter<'a, AccountInfo<'b>>,
+        nft_infos: &[AccountInfo<'b>; 6],
+        employee: &BOQEmployee,
+        nft_metadata: &Metadata,
+    ) -> ProgramResult {
+        let [
+            staker_info, 
+            nft_token_info, 
+            employee_info, 
+            nft_mint_info, 
+            nft_metadata_info, 
+            nft_token_program_info,
+        ] = nft_infos;
+
+        // The employee's NFT escrow account.
+        let escrow_info = next_account_info(account_info_iter)?;
+        let nft_token_program_id = nft_token_program_info.key;
+        Check::ata(escrow_info, nft_mint_info.key, employee_info.key, nft_token_program_id)?;
+
+        // The system program.
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        // The associated token program.
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+
+        // Creates the escrow account if it does not exist.
+        invoke(
+            &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                staker_info.key, 
+                employee_info.key, 
+                nft_mint_info.key, 
+                nft_token_program_info.key,
+            ), 
+            &[
+                staker_info.clone(),
+                escrow_info.clone(),
+                employee_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                nft_token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        if Self::_is_programmable(nft_metadata) {
+            return Self::_process_programmable_transfer(
+                account_info_iter, 
+                nft_metadata, 
+                employee, 
+                [nft_token_info, staker_info, escrow_info, employee_info], 
+                nft_mint_info, 
+                nft_metadata_info, 
+                staker_info, 
+                system_program_info, 
+                nft_token_program_info, 
+                associated_token_program_info,
+                false,
+            );
+        }
+
+        invoke(
+            &spl_token_2022::instruction::transfer_checked(
+                nft_token_program_info.key, 
+                nft_token_info.key, 
+                nft_mint_info.key, 
+                escrow_info.key, 
+                staker_info.key, 
+                &[], 
+                1, 
+                0,
+            )?,
+            &[
+                nft_token_info.clone(),
+                nft_mint_info.clone(),
+                escrow_info.clone(),
+                staker_info.clone(),
+                nft_token_program_info.clone(),
+            ],
+        )
+    }
+
+    /**
+     * Returns the escrowed NFT to the staker's associated token account and closes the escrow 
+     * account, refunding its rent to the staker.
+     * 
+     * The escrow account of a programmable NFT stays open, as it remains frozen by the token 
+     * metadata program.
+     * 
+     * `nft_infos` holds the staker, NFT token, employee, NFT mint, NFT metadata and NFT token 
+     * program accounts.
+     */
+    fn _process_release_nft<'a, 'b: 'a>(
+        account_info_iter: &mut Iter<'a, AccountInfo<'b>>,
+        nft_infos: &[AccountInfo<'b>; 6],
+        employee: &BOQEmployee,
+        nft_metadata: &Metadata,
+    ) -> ProgramResult {
+        let [
+            staker_info, 
+            nft_token_info, 
+            employee_info, 
+            nft_mint_info, 
+            nft_metadata_info, 
+            nft_token_program_info,
+        ] = nft_infos;
+
+        // The employee's NFT escrow account.
+        let escrow_info = next_account_info(account_info_iter)?;
+        let nft_token_program_id = nft_token_program_info.key;
+        Check::ata(escrow_info, nft_mint_info.key, employee_info.key, nft_token_program_id)?;
+
+        // The system program.
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        // The associated token program.
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+
+        // Creates the staker's NFT token account if it does not exist.
+        invoke(
+            &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                staker_info.key, 
+                staker_info.key, 
+                nft_mint_info.key, 
+                nft_token_program_info.key,
+            ), 
+            &[
+                staker_info.clone(),
+                nft_token_info.clone(),
+                staker_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                nft_token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        if Self::_is_programmable(nft_metadata) {
+            return Self::_process_programmable_transfer(
+                account_info_iter, 
+                nft_metadata, 
+                employee, 
+                [escrow_info, employee_info, nft_token_info, staker_info], 
+                nft_mint_info, 
+                nft_metadata_info, 
+                staker_info, 
+                system_program_info, 
+                nft_token_program_info, 
+                associated_token_program_info,
+                true,
+            );
+        }
+
+        // Return the NFT to the staker.
+        let employee_seeds = &[
+            BOQSeed::EMPLOYEE.as_bytes(), 
+            nft_mint_info.key.as_ref(), 
+            &[employee.bump],
+        ];
+        invoke_signed(
+            &spl_token_2022::instruction::transfer_checked(
+                nft_token_program_info.key, 
+                escrow_info.key, 
+                nft_mint_info.key, 
+                nft_token_info.key, 
+                employee_info.key, 
+                &[], 
+                1, 
+                0,
+            )?,
+            &[
+                escrow_info.clone(),
+                nft_mint_info.clone(),
+                nft_token_info.clone(),
+                employee_info.clone(),
+                nft_token_program_info.clone(),
+            ],
+            &[
+                employee_seeds,
+            ],
+        )?;
+
+        // Close the escrow account and refund its rent to the staker.
+        invoke_signed(
+            &spl_token_2022::instruction::close_account(
+                nft_token_program_info.key, 
+                escrow_info.key, 
+                staker_info.key, 
+                employee_info.key, 
+                &[], 
+            )?,
+            &[
+                escrow_info.clone(),
+                staker_info.clone(),
+                employee_info.clone(),
+                nft_token_program_info.clone(),
+            ],
+            &[
+                employee_seeds,
+            ],
+        )
+    }
+
+    /**
+     * Approves the employee PDA as the delegate of the staker's NFT token account and freezes it 
+     * through the token metadata program.
+     * 
+     * A programmable NFT is delegated to the employee PDA as a staking delegate and locked.
+     * 
+     * `nft_infos` holds the staker, NFT token, employee, NFT mint, NFT metadata and NFT token 
+     * program accounts.
+     * 
+     * Throws a [ProgramError] if the NFT is not an SPL Token NFT.
+     */
+    fn _process_freeze_nft<'a, 'b: 'a>(
+        account_info_iter: &mut Iter<'a, AccountInfo<'b>>,
+        nft_infos: &[AccountInfo<'b>; 6],
+        employee: &BOQEmployee,
+        nft_metadata: &Metadata,
+    ) -> ProgramResult {
+        let [
+            staker_info, 
+            nft_token_info, 
+            employee_info, 
+            nft_mint_info, 
+            nft_metadata_info, 
+            nft_token_program_info,
+        ] = nft_infos;
+
+        // The NFT's master edition account.
+        let nft_edition_info = next_account_info(account_info_iter)?;
+
+        // The token metadata program.
+        let token_metadata_program_info = next_account_info(account_info_iter)?;
+        Check::pubkey(token_metadata_program_info.key, &mpl_token_metadata::ID)?;
+        Check::pubkey(nft_token_program_info.key, &spl_token::ID)?;
+
+        let employee_seeds = &[
+            BOQSeed::EMPLOYEE.as_bytes(), 
+            nft_mint_info.key.as_ref(), 
+            &[employee.bump],
+        ];
+
+        if Self::_is_programmable(nft_metadata) {
+
+            // The programmable NFT's token record, rule set and supporting accounts.
+            let [
+                token_record_info, 
+                system_program_info, 
+                sysvar_instructions_info, 
+                authorization_rules_program_info, 
+                authorization_rules_info,
+            ] = Self::_next_programmable_accounts(account_info_iter, nft_metadata)?;
+            let authorization_rules = Self::_authorization_rules(nft_metadata);
+
+            // Delegate the NFT to the employee PDA.
+            let mut builder = DelegateBuilder::new();
+            builder
+                .delegate(*employee_info.key)
+                .metadata(*nft_metadata_info.key)
+                .master_edition(*nft_edition_info.key)
+                .token_record(*token_record_info.key)
+                .mint(*nft_mint_info.key)
+                .token(*nft_token_info.key)
+                .authority(*staker_info.key)
+                .payer(*staker_info.key)
+                .spl_token_program(*nft_token_program_info.key);
+            if let Some(authorization_rules) = authorization_rules {
+                builder
+                    .authorization_rules_program(*authorization_rules_program_info.key)
+                    .authorization_rules(authorization_rules);
+            }
+            invoke(
+                &builder
+                    .build(DelegateArgs::StakingV1 { amount: 1, authorization_data: None })
+                    .map_err(|_| ProgramError::InvalidArgument)?
+                    .instruction(),
+                &[
+                    employee_info.clone(),
+                    nft_metadata_info.clone(),
+                    nft_edition_info.clone(),
+                    token_record_info.clone(),
+                    nft_mint_info.clone(),
+                    nft_token_info.clone(),
+                    staker_info.clone(),
+                    system_program_info.clone(),
+                    sysvar_instructions_info.clone(),
+                    nft_token_program_info.clone(),
+                    authorization_rules_program_info.clone(),
+                    authorization_rules_info.clone(),
+                    token_metadata_program_info.clone(),
+                ],
+            )?;
+
+            // Lock the NFT in the staker's wallet.
+            let mut builder = LockBuilder::new();
+            builder
+                .authority(*employee_info.key)
+                .token_owner(*staker_info.key)
+                .token(*nft_token_info.key)
+                .mint(*nft_mint_info.key)
+                .metadata(*nft_metadata_info.key)
+                .edition(*nft_edition_info.key)
+                .token_record(*token_record_info.key)
+                .payer(*staker_info.key)
+                .spl_token_program(*nft_token_program_info.key);
+            if let Some(authorization_rules) = authorization_rules {
+                builder
+                    .authorization_rules_program(*authorization_rules_program_info.key)
+                    .authorization_rules(authorization_rules);
+            }
+            return invoke_signed(
+                &builder
+                    .build(LockArgs::V1 { authorization_data: None })
+                    .map_err(|_| ProgramError::InvalidArgument)?
+                    .instruction(),
+                &[
+                    employee_info.clone(),
+                    staker_info.clone(),
+                    nft_token_info.clone(),
+                    nft_mint_info.clone(),
+                    nft_metadata_info.clone(),
+                    nft_edition_info.clone(),
+                    token_record_info.clone(),
+                    system_program_info.clone(),
+                    sysvar_instructions_info.clone(),
+                    nft_token_program_info.clone(),
+                    authorization_rules_program_info.clone(),
+                    authorization_rules_info.clone(),
+                    token_metadata_program_info.clone(),
+                ],
+                &[
+                    employee_seeds,
+                ],
+            );
+        }
+
+        // Approve the employee PDA as the delegate of the NFT.
+        invoke(
+            &spl_token_2022::instruction::approve_checked(
+                nft_token_program_info.key, 
+                nft_token_info.key, 
+                nft_mint_info.key, 
+                employee_info.key, 
+                staker_info.key, 
+                &[], 
+                1, 
+                0,
+            )?,
+            &[
+                nft_token_info.clone(),
+                nft_mint_info.clone(),
+                employee_info.clone(),
+                staker_info.clone(),
+                nft_token_program_info.clone(),
+            ],
+        )?;
+
+        // Freeze the NFT in the staker's wallet.
+        invoke_signed(
+            &mpl_token_metadata::instruction::freeze_delegated_account(
+                mpl_token_metadata::ID, 
+                *employee_info.key, 
+                *nft_token_info.key, 
+                *nft_edition_info.key, 
+                *nft_mint_info.key,
+            ),
+            &[
+                employee_info.clone(),
+                nft_token_info.clone(),
+                nft_edition_info.clone(),
+                nft_mint_info.clone(),
+                nft_token_program_info.clone(),
+                token_metadata_program_info.clone(),
+            ],
+            &[
+                employee_seeds,
+            ],
+        )
+    }
+
+    /**
+     * Thaws the staker's NFT token account through the token metadata program and revokes the 
+     * employee PDA's delegation.
+     * 
+     * A programmable NFT is unlocked and its staking delegate is revoked.
+     * 
+     * `nft_infos` holds the staker, NFT token, employee, NFT mint, NFT metadata and NFT token 
+     * program accounts.
+     */
+    fn _process_thaw_nft<'a, 'b: 'a>(
+        account_info_iter: &mut Iter<'a, AccountInfo<'b>>,
+        nft_infos: &[AccountInfo<'b>; 6],
+        employee: &BOQEmployee,
+        nft_metadata: &Metadata,
+    ) -> ProgramResult {
+        let [
+            staker_info, 
+            nft_token_info, 
+            employee_info, 
+            nft_mint_info, 
+            nft_metadata_info, 
+            nft_token_program_info,
+        ] = nft_infos;
+
+        // The NFT's master edition account.
+        let nft_edition_info = next_account_info(account_info_iter)?;
+
+        // The token metadata program.
+        let token_metadata_program_info = next_account_info(account_info_iter)?;
+        Check::pubkey(token_metadata_program_info.key, &mpl_token_metadata::ID)?;
+
+        let employee_seeds = &[
+            BOQSeed::EMPLOYEE.as_bytes(), 
+            nft_mint_info.key.as_ref(), 
+            &[employee.bump],
+        ];
+
+        if Self::_is_programmable(nft_metadata) {
+
+            // The programmable NFT's token record, rule set and supporting accounts.
+            let [
+                token_record_info, 
+                system_program_info, 
+                sysvar_instructions_info, 
+                authorization_rules_program_info, 
+                authorization_rules_info,
+            ] = Self::_next_programmable_accounts(account_info_iter, nft_metadata)?;
+            let authorization_rules = Self::_authorization_rules(nft_metadata);
+
+            // Unlock the NFT.
+            let mut builder = UnlockBuilder::new();
+            builder
+                .authority(*employee_info.key)
+                .token_owner(*staker_info.key)
+                .token(*nft_token_info.key)
+                .mint(*nft_mint_info.key)
+                .metadata(*nft_metadata_info.key)
+                .edition(*nft_edition_info.key)
+                .token_record(*token_record_info.key)
+                .payer(*staker_info.key)
+                .spl_token_program(*nft_token_program_info.key);
+            if let Some(authorization_rules) = authorization_rules {
+                builder
+                    .authorization_rules_program(*authorization_rules_program_info.key)
+                    .authorization_rules(authorization_rules);
+            }
+            invoke_signed(
+                &builder
+                    .build(UnlockArgs::V1 { authorization_data: None })
+                    .map_err(|_| ProgramError::InvalidArgument)?
+                    .instruction(),
+                &[
+                    employee_info.clone(),
+                    staker_info.clone(),
+                    nft_token_info.clone(),
+                    nft_mint_info.clone(),
+                    nft_metadata_info.clone(),
+                    nft_edition_info.clone(),
+                    token_record_info.clone(),
+                    system_program_info.clone(),
+                    sysvar_instructions_info.clone(),
+                    nft_token_program_info.clone(),
+                    authorization_rules_program_info.clone(),
+                    authorization_rules_info.clone(),
+                    token_metadata_program_info.clone(),
+                ],
+                &[
+                    employee_seeds,
+                ],
+            )?;
+
+            // Revoke the employee PDA's staking delegate.
+            let mut builder = RevokeBuilder::new();
+            builder
+                .delegate(*employee_info.key)
+                .metadata(*nft_metadata_info.key)
+                .master_edition(*nft_edition_info.key)
+                .token_record(*token_record_info.key)
+                .mint(*nft_mint_info.key)
+                .token(*nft_token_info.key)
+                .authority(*staker_info.key)
+                .payer(*staker_info.key)
+                .spl_token_program(*nft_token_program_info.key);
+            if let Some(authorization_rules) = authorization_rules {
+                builder
+                    .authorization_rules_program(*authorization_rules_program_info.key)
+                    .authorization_rules(authorization_rules);
+            }
+            return invoke(
+                &builder
+                    .build(RevokeArgs::StakingV1)
+                    .map_err(|_| ProgramError::InvalidArgument)?
+                    .instruction(),
+                &[
+                    employee_info.clone(),
+                    nft_metadata_info.clone(),
+                    nft_edition_info.clone(),
+                    token_record_info.clone(),
+                    nft_mint_info.clone(),
+                    nft_token_info.clone(),
+                    staker_info.clone(),
+                    system_program_info.clone(),
+                    sysvar_instructions_info.clone(),
+                    nft_token_program_info.clone(),
+                    authorization_rules_program_info.clone(),
+                    authorization_rules_info.clone(),
+                    token_metadata_program_info.clone(),
+                ],
+            );
+        }
+
+        // Thaw the NFT.
+        invoke_signed(
+            &mpl_token_metadata::instruction::thaw_delegated_account(
+                mpl_token_metadata::ID, 
+                *employee_info.key, 
+                *nft_token_info.key, 
+                *nft_edition_info.key, 
+                *nft_mint_info.key,
+            ),
+            &[
+                employee_info.clone(),
+                nft_token_info.clone(),
+                nft_edition_info.clone(),
+                nft_mint_info.clone(),
+                nft_token_program_info.clone(),
+                token_metadata_program_info.clone(),
+            ],
+            &[
+                employee_seeds,
+            ],
+        )?;
+
+        // Revoke the employee PDA's delegation.
+        invoke(
+            &spl_token_2022::instruction::revoke(
+                nft_token_program_info.key, 
+                nft_token_info.key, 
+                staker_info.key, 
+                &[], 
+            )?,
+            &[
+                nft_token_info.clone(),
+                staker_info.clone(),
+                nft_token_program_info.clone(),
+            ],
+        )
+    }
+
+    /**
+     * Transfers a programmable NFT through the token metadata program.
+     * 
+     * `token_infos` holds the source token, source owner, destination token and destination owner 
+     * accounts. When `from_escrow` is set the transfer is signed by the employee PDA, which owns 
+     * the source escrow account. Programmable NFTs are always SPL Token accounts, so 
+     * `nft_token_program_info` must be the SPL Token program.
+     */
+    fn _process_programmable_transfer<'a, 'b: 'a>(
+        account_info_iter: &mut Iter<'a, AccountInfo<'b>>,
+        nft_metadata: &Metadata,
+        employee: &BOQEmployee,
+        token_infos: [&AccountInfo<'b>; 4],
+        nft_mint_info: &AccountInfo<'b>,
+        nft_metadata_info: &AccountInfo<'b>,
+        payer_info: &AccountInfo<'b>,
+        system_program_info: &AccountInfo<'b>,
+        nft_token_program_info: &AccountInfo<'b>,
+        associated_token_program_info: &AccountInfo<'b>,
+        from_escrow: bool,
+    ) -> ProgramResult {
+        let [
+            source_info, 
+            source_owner_info, 
+            destination_info, 
+            destination_owner_info,
+        ] = token_infos;
+
+        // The NFT's master edition account.
+        let nft_edition_info = next_account_info(account_info_iter)?;
+
+        // The token metadata program.
+        let token_metadata_program_info = next_account_info(account_info_iter)?;
+        Check::pubkey(token_metadata_program_info.key, &mpl_token_metadata::ID)?;
+        Check::pubkey(nft_token_program_info.key, &spl_token::ID)?;
+
+        // The source token record account.
+        let source_token_record_info = next_account_info(account_info_iter)?;
+
+        // The destination token record account.
+        let destination_token_record_info = next_account_info(account_info_iter)?;
+
+        // The instructions sysvar account.
+        let sysvar_instructions_info = next_account_info(account_info_iter)?;
+
+        // The token auth rules program.
+        let authorization_rules_program_info = next_account_info(account_info_iter)?;
+
+        // The NFT's rule set account.
+        let authorization_rules_info = next_account_info(account_info_iter)?;
+        let authorization_rules = Self::_authorization_rules(nft_metadata);
+        if let Some(authorization_rules) = authorization_rules {
+            Check::pubkey(authorization_rules_info.key, &authorization_rules)?;
+        }
+
+        let mut builder = TransferBuilder::new();
+        builder
+            .token(*source_info.key)
+            .token_owner(*source_owner_info.key)
+            .destination(*destination_info.key)
+            .destination_owner(*destination_owner_info.key)
+            .mint(*nft_mint_info.key)
+            .metadata(*nft_metadata_info.key)
+            .edition(*nft_edition_info.key)
+            .owner_token_record(*source_token_record_info.key)
+            .destination_token_record(*destination_token_record_info.key)
+            .authority(*source_owner_info.key)
+            .payer(*payer_info.key)
+            .spl_token_program(*nft_token_program_info.key);
+        if let Some(authorization_rules) = authorization_rules {
+            builder
+                .authorization_rules_program(*authorization_rules_program_info.key)
+                .authorization_rules(authorization_rules);
+        }
+        let instruction = builder
+            .build(TransferArgs::V1 { amount: 1, authorization_data: None })
+            .map_err(|_| ProgramError::InvalidArgument)?
+            .instruction();
+        let account_infos = &[
+            source_info.clone(),
+            source_owner_info.clone(),
+            destination_info.clone(),
+            destination_owner_info.clone(),
+            nft_mint_info.clone(),
+            nft_metadata_info.clone(),
+            nft_edition_info.clone(),
+            source_token_record_info.clone(),
+            destination_token_record_info.clone(),
+            payer_info.clone(),
+            system_program_info.clone(),
+            sysvar_instructions_info.clone(),
+            nft_token_program_info.clone(),
+            associated_token_program_info.clone(),
+            authorization_rules_program_info.clone(),
+            authorization_rules_info.clone(),
+            token_metadata_program_info.clone(),
+        ];
+
+        if from_escrow {
+            let employee_seeds = &[
+                BOQSeed::EMPLOYEE.as_bytes(), 
+                nft_mint_info.key.as_ref(), 
+                &[employee.bump],
+            ];
+            invoke_signed(&instruction, account_infos, &[employee_seeds])
+        } else {
+            invoke(&instruction, account_infos)
+        }
+    }
+
+    /**
+     * Returns the token record, system program, instructions sysvar, token auth rules program and 
+     * rule set accounts of a programmable NFT.
+     * 
+     * Throws a [ProgramError] if the rule set account does not match the NFT's rule set.
+     */
+    fn _next_programmable_accounts<'a, 'b: 'a>(
+        account_info_iter: &mut Iter<'a, AccountInfo<'b>>,
+        nft_metadata: &Metadata,
+    ) -> Result<[&'a AccountInfo<'b>; 5], ProgramError> {
+
+        // The token record account.
+        let token_record_info = next_account_info(account_info_iter)?;
+
+        // The system program.
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        // The instructions sysvar account.
+        let sysvar_instructions_info = next_account_info(account_info_iter)?;
+
+        // The token auth rules program.
+        let authorization_rules_program_info = next_account_info(account_info_iter)?;
+
+        // The NFT's rule set account.
+        let authorization_rules_info = next_account_info(account_info_iter)?;
+        if let Some(authorization_rules) = Self::_authorization_rules(nft_metadata) {
+            Check::pubkey(authorization_rules_info.key, &authorization_rules)?;
+        }
+
+        Ok([
+            token_record_info, 
+            system_program_info, 
+            sysvar_instructions_info, 
+            authorization_rules_program_info, 
+            authorization_rules_info,
+        ])
+    }
+
+    /**
+     * Returns true if `nft_metadata` describes a programmable NFT.
+     */
+    fn _is_programmable(
+        nft_metadata: &Metadata,
+    ) -> bool {
+        nft_metadata.token_standard == Some(TokenStandard::ProgrammableNonFungible)
+    }
+
+    /**
+     * Returns the rule set of a programmable NFT, if any.
+     */
+    fn _authorization_rules(
+        nft_metadata: &Metadata,
+    ) -> Option<Pubkey> {
+        match nft_metadata.programmable_config {
+            Some(ProgrammableConfig::V1 { rule_set }) => rule_set,
+            None => None,
+        }
+    }
+
+    /**
+     * Updates the URI of an employee's NFT to show its `level`, appending a `level` query 
+     * parameter that replaces any previous one.
+     * 
+     * The metadata is updated by the employer PDA as a data delegate of the employer's collection.
+     * The update is skipped, without failing the shift, if the employer PDA is not a data delegate 
+     * of the NFT's collection or the new URI is longer than the token metadata program allows. The 
+     * level is still recorded on the employee and shown by the NFT's next update.
+     * 
+     * `metadata_infos` holds the token metadata program, delegate record, system program and 
+     * instructions sysvar accounts. `nft_infos` holds the NFT mint and metadata accounts.
+     */
+    fn _process_update_level<'a>(
+        employer_info: &AccountInfo<'a>,
+        employer: &BOQEmployer,
+        metadata_infos: [&AccountInfo<'a>; 4],
+        nft_infos: [&AccountInfo<'a>; 2],
+        level: u8,
+    ) -> ProgramResult {
+        let [
+            token_metadata_program_info, 
+            delegate_record_info, 
+            system_program_info, 
+            sysvar_instructions_info,
+        ] = metadata_infos;
+        let [nft_mint_info, nft_metadata_info] = nft_infos;
+
+        // The NFT's metadata.
+        Check::owner(nft_metadata_info, &mpl_token_metadata::ID)?;
+        let nft_metadata = Metadata::from_account_info(nft_metadata_info)?;
+        Check::pubkey(&nft_metadata.mint, nft_mint_info.key)?;
+
+        // Replace the level query parameter of the URI.
+        let uri = nft_metadata.data.uri.trim_matches(char::from(0));
+        let base_uri = uri
+            .rsplit_once("?level=")
+            .or_else(|| uri.rsplit_once("&level="))
+            .map_or(uri, |(base_uri, _)| base_uri);
+        let separator = if base_uri.contains('?') { '&' } else { '?' };
+        let data = Data {
+            name: nft_metadata.data.name.trim_matches(char::from(0)).to_string(),
+            symbol: nft_metadata.data.symbol.trim_matches(char::from(0)).to_string(),
+            uri: format!("{}{}level={}", base_uri, separator, level),
+            seller_fee_basis_points: nft_metadata.data.seller_fee_basis_points,
+            creators: nft_metadata.data.creators,
+        };
+        if data.uri.len() > MAX_URI_LENGTH {
+            msg!("Skipped level update: URI too long for {}", nft_mint_info.key);
+            return Ok(());
+        }
+
+        // Check that the employer PDA is a data delegate of the NFT's collection.
+        let is_collection = nft_metadata.collection.map_or(false, |collection| {
+            collection.verified && collection.key.eq(&employer.collection_mint)
+        });
+        let (delegate_record_key, _) = find_metadata_delegate_record_account(
+            &employer.collection_mint, 
+            MetadataDelegateRole::Data, 
+            &nft_metadata.update_authority, 
+            employer_info.key,
+        );
+        let is_delegate = delegate_record_info.key.eq(&delegate_record_key)
+            && delegate_record_info.owner.eq(&mpl_token_metadata::ID)
+            && !delegate_record_info.data_is_empty();
+        if !is_collection || !is_delegate {
+            msg!("Skipped level update: employer not a data delegate of {}", nft_mint_info.key);
+            return Ok(());
+        }
+
+        let mut builder = UpdateBuilder::new();
+        builder
+            .authority(*employer_info.key)
+            .delegate_record(*delegate_record_info.key)
+            .mint(*nft_mint_info.key)
+            .metadata(*nft_metadata_info.key)
+            .payer(*employer_info.key)
+            .system_program(*system_program_info.key)
+            .sysvar_instructions(*sysvar_instructions_info.key);
+        let employer_seeds = &[BOQSeed::EMPLOYER.as_bytes(), &[employer.bump]];
+
+        invoke_signed(
+            &builder
+                .build(UpdateArgs::AsDataDelegateV2 { data: Some(data), authorization_data: None })
+                .map_err(|_| ProgramError::InvalidArgument)?
+                .instruction(),
+            &[
+                employer_info.clone(),
+                delegate_record_info.clone(),
+                nft_mint_info.clone(),
+                nft_metadata_info.clone(),
+                system_program_info.clone(),
+                sysvar_instructions_info.clone(),
+                token_metadata_program_info.clone(),
+            ],
+            &[
+                employer_seeds,
+            ],
+        )
+    }
+
+    /**
+     * Returns the wallet that `employee` is working for, or [None] if `nft_token` does not hold 
+     * the employee's NFT in a way that allows it to work shifts under the employer's staking mode.
+     */
+    fn _holder(
+        employer: &BOQEmployer,
+        employee_info: &AccountInfo,
+        employee: &BOQEmployee,
+        nft_token: &Account,
+    ) -> Option<Pubkey> {
+        if nft_token.amount != 1 || nft_token.mint.ne(&employee.nft_mint) {
+            return None;
+        }
+        match employer.staking_mode {
+
+            // The NFT works from the holder's wallet.
+            BOQStakingMode::None => {
+                Some(nft_token.owner)
+            },
+
+            // The NFT only works while it is escrowed by the employee.
+            BOQStakingMode::Custodial => {
+                if employee.is_staked && nft_token.owner.eq(employee_info.key) {
+                    Some(employee.staker)
+                } else {
+                    None
+                }
+            },
+
+            // The NFT only works while it is frozen in the staker's wallet by the employee.
+            BOQStakingMode::Freeze => {
+                let is_delegate = nft_token.delegate == COption::Some(*employee_info.key);
+                if employee.is_staked && nft_token.is_frozen() && is_delegate {
+                    Some(nft_token.owner)
+                } else {
+                    None
+                }
+            },
+        }
+    }
+
+    /**
+     * Credits the wage accrued by `employee` up to `slot` to the claimable balance of its recorded 
+     * holder if `holder` differs from it, then records `holder` as the employee's holder.
+     * 
+     * The recorded holder's shift account is read from `account_info_iter` only when the holder 
+     * has changed. If that account has not been created, the pending wage is forfeited and the 
+     * employee is clocked in at `slot`. Shift accounts are only created with their canonical bump 
+     * (see [Processor::process_create_shift]), and legacy shift accounts with another bump are 
+     * moved to their canonical address before they can be used (see [Processor::process_migrate]), 
+     * so an empty canonical address proves that the recorded holder has no shift account.
+     * 
+     * If the recorded holder is the operator of `rental`, it is only paid up to the end of the 
+     * agreement, and the rental owner's share of the wage is withheld from it and returned, to be 
+     * credited with [Processor::_credit_rental_owner].
+     * 
+     * Throws a [ProgramError] if the shift account is not the recorded holder's shift PDA account.
+     */
+    fn _checkpoint<'a, 'b: 'a>(
+        program_id: &Pubkey,
+        account_info_iter: &mut Iter<'a, AccountInfo<'b>>,
+        employer: &mut BOQEmployer,
+        schedule: Option<&BOQSchedule>,
+        rental: Option<&BOQRental>,
+        employee: &mut BOQEmployee,
+        holder: &Pubkey,
+        slot: Slot,
+    ) -> Result<Option<u64>, ProgramError> {
+        let previous_holder = employee.holder;
+        if previous_holder == Pubkey::default() || previous_holder.eq(holder) {
+            employee.holder = *holder;
+            return Ok(None);
+        }
+
+        // An operator is only paid up to the end of its agreement. As for any other holder, only 
+        // the slots of the last shift are paid, so that settling an agreement that expired long 
+        // ago never needs replaced rate changes or bonus windows.
+        let slot = match rental {
+            Some(rental) if rental.operator.eq(&previous_holder) => {
+                let last_shift_slot = slot.saturating_sub(employer.slots_per_shift);
+                employee.last_slot = employee.last_slot.max(last_shift_slot);
+                min(slot, rental.end_slot)
+            },
+            _ => slot,
+        };
+
+        let mut owner_amount = None;
+
+        // The previous holder's `shift` PDA account.
+        let previous_shift_info = next_account_info(account_info_iter)?;
+        let previous_shift_seeds = &[BOQSeed::SHIFT.as_ref(), previous_holder.as_ref()];
+
+        if previous_shift_info.owner.eq(program_id) {
+            let previous_shift_data = &mut previous_shift_info.data.borrow_mut();
+            let mut previous_shift = BOQShift::try_from_slice(&previous_shift_data)?;
+            Check::initialized(&previous_shift, previous_shift_info)?;
+            let previous_shift_key = Pubkey::create_program_address(
+                &[previous_shift_seeds[0], previous_shift_seeds[1], &[previous_shift.bump]], 
+                program_id,
+            )?;
+            Check::pubkey(previous_shift_info.key, &previous_shift_key)?;
+            let mut amount = Self::_work_shift(
+                employer, 
+                schedule, 
+                employee, 
+                &mut previous_shift, 
+                slot,
+            );
+
+            // Withhold the rental owner's share of the operator's wage.
+            if let Some(rental) = rental.filter(|rental| rental.operator.eq(&previous_holder)) {
+                let rental_amount = rental.owner_amount(amount);
+                previous_shift.total_rewards -= rental_amount;
+                amount -= rental_amount;
+                owner_amount = Some(rental_amount);
+            }
+
+            previous_shift.claimable += amount;
+            previous_shift.serialize(&mut &mut previous_shift_data[..])?;
+        } else {
+            let (previous_shift_key, _) = Pubkey::find_program_address(
+                previous_shift_seeds, 
+                program_id,
+            );
+            Check::pubkey(previous_shift_info.key, &previous_shift_key)?;
+            employer.clock_in(employee, slot);
+        }
+
+        employee.holder = *holder;
+        Ok(owner_amount)
+    }
+
+    /**
+     * Credits `amount`, the rental owner's share of an operator's wage, to the shift account of 
+     * the owner of `rental`.
+     * 
+     * If `shift` belongs to the rental owner, it is credited in place. Otherwise the rental 
+     * owner's shift account is read from `account_info_iter`.
+     * 
+     * Throws a [ProgramError] if the shift account is not the rental owner's shift PDA account.
+     */
+    fn _credit_rental_owner<'a, 'b: 'a>(
+        program_id: &Pubkey,
+        account_info_iter: &mut Iter<'a, AccountInfo<'b>>,
+        rental: &BOQRental,
+        shift: Option<&mut BOQShift>,
+        amount: u64,
+    ) -> ProgramResult {
+        if let Some(shift) = shift.filter(|shift| shift.owner.eq(&rental.owner)) {
+            shift.total_rewards += amount;
+            shift.claimable += amount;
+            return Ok(());
+        }
+
+        // The rental owner's `shift` PDA account.
+        let owner_shift_info = next_account_info(account_info_iter)?;
+        Check::owner(&owner_shift_info, program_id)?;
+        let owner_shift_data = &mut owner_shift_info.data.borrow_mut();
+        let mut owner_shift = BOQShift::try_from_slice(&owner_shift_data)?;
+        Check::initialized(&owner_shift, owner_shift_info)?;
+        let owner_shift_seeds = &[
+            BOQSeed::SHIFT.as_ref(), 
+            rental.owner.as_ref(), 
+            &[owner_shift.bump],
+        ];
+        Check::pda(program_id, owner_shift_info, owner_shift_seeds)?;
+
+        owner_shift.total_rewards += amount;
+        owner_shift.claimable += amount;
+        owner_shift.serialize(&mut &mut owner_shift_data[..])?;
+        Ok(())
+    }
+
+    /**
+     * Works `employee` up to `slot`, recording the worked slots and earnings on `employee` and 
+     * `shift`, and grants the bonus of any milestones reached.
+     * 
+     * Returns the amount earned, truncated to the employer's remaining emission.
+     */
+    fn _work_shift(
+        employer: &mut BOQEmployer,
+        schedule: Option<&BOQSchedule>,
+        employee: &mut BOQEmployee,
+        shift: &mut BOQShift,
+        slot: Slot,
+    ) -> u64 {
+        let slots_per_shift = employer.slots_per_shift;
+
+        // Calculate the base rate.
+        let elapsed_slots = slot.saturating_sub(employee.last_slot);
+        let available_slots = min(elapsed_slots, slots_per_shift);
+        let total_slots = employee.total_slots;
+        if available_slots == 0 {
+            return 0;
+        }
+
+        let employee_total_slots = total_slots + available_slots;
+
+        // Update the attendance streak.
+        employee.update_streak(employer, slot);
+
+        let earnings = match employer.reward_mode {
+
+            // Each employee earns the base rate independently.
+            BOQRewardMode::Employee => {
+                let from_slot = slot - available_slots;
+                let base_rate = employer.base_amount(from_slot, slot) 
+                    + employee.lock_bonus_amount(employer, from_slot, slot)
+                    + employer.bonus_amount(from_slot, slot, employee.rarity_multiplier)
+                    + employer.bonus_amount(
+                        from_slot, 
+                        slot, 
+                        employer.streak_multiplier(employee.streak),
+                    )
+                    + employer.bonus_amount(
+                        from_slot, 
+                        slot, 
+                        employer.level_multiplier(employee.level),
+                    )
+                    + schedule.map_or(0, |schedule| {
+                        schedule.bonus_amount(employer, from_slot, slot)
+                    })
+                    + employee.boost_bonus_amount(employer, from_slot, slot);
+
+                // Calculate the inflation rate.
+                let inflation_rate = if employee_total_slots < slots_per_shift {
+                    0
+                } else {
+                    let current_shift = total_slots / slots_per_shift;
+                    let next_shift = current_shift + 1;
+                    let shift_boundary = next_shift * slots_per_shift;
+                    let next_shift_slots = if employee_total_slots > shift_boundary { 
+                        employee_total_slots % slots_per_shift
+                    } else {
+                        0
+                    };
+                    let current_shift_slots = available_slots - next_shift_slots;
+                    let next_shift_slot = from_slot + current_shift_slots;
+                    (employer.inflation_amount(from_slot, next_shift_slot) * current_shift)
+                    + (employer.inflation_amount(next_shift_slot, slot) * next_shift)
+                };
+
+                base_rate + inflation_rate
+            },
+
+            // Each employee earns an equal share of the employer's pool.
+            BOQRewardMode::Pool => {
+                employer.claim_pool(employee)
+            },
+        };
+
+        // // Calculate the bonus.
+        // let employee_shifts = employee.total_shifts(&employer);
+        // let completed_shifts = min(employee_shifts, current_shift);
+        // let bonus_rate = completed_shifts * employer.rate_increase_per_shift;
+
+        employee.last_slot = slot;
+        employee.total_slots = employee_total_slots;
+
+        // Grant the one-time bonus of the milestones reached.
+        let milestone_bonus = employee.grant_milestones(employer);
+
+        // Truncate the amount to the employer's remaining emission.
+        let amount = employer.emit(earnings + milestone_bonus);
+
+        shift.total_slots += available_slots;
+        shift.total_rewards += amount;
+
+        // Level up.
+        let level = employer.level(employee_total_slots);
+        if level > employee.level {
+            msg!("Level up: employee {} reached level {}", employee.nft_mint, level);
+            employee.level = level;
+        }
+
+        amount
+    }
+
+    /**
+     * Pays out `amount` tokens withdrawn from `shift` to the owner's `ata_info` token account.
+     * 
+     * If the employer charges a fee, the employer's treasury token account is read from 
+     * `account_info_iter` and paid the employer's `fee_rate` of `amount`, which is recorded in the 
+     * employer's `total_fees`.
+     * 
+     * If the shift has a split table, the associated token account of each recipient is read next, 
+     * in split order, and paid the recipient's share of the amount left after the fee. The owner 
+     * receives the remainder.
+     */
+    fn _process_withdrawal<'a, 'b: 'a>(
+        program_id: &Pubkey,
+        account_info_iter: &mut Iter<'a, AccountInfo<'b>>,
+        payout_info: &AccountInfo<'b>,
+        employer_info: &AccountInfo<'b>,
+        employer: &mut BOQEmployer,
+        shift: &BOQShift,
+        token_mint_info: &AccountInfo<'b>,
+        ata_info: &AccountInfo<'b>,
+        token_program_info: &AccountInfo<'b>,
+        amount: u64,
+    ) -> ProgramResult {
+        let mut remainder = amount;
+
+        if employer.fee_rate > 0 {
+
+            // The employer's treasury token account.
+            let treasury_info = next_account_info(account_info_iter)?;
+            Check::pubkey(treasury_info.key, &employer.treasury)?;
+
+            let fee = employer.fee_amount(amount);
+            employer.total_fees += fee;
+            employer.serialize(&mut &mut employer_info.data.borrow_mut()[..])?;
+            remainder -= fee;
+
+            Self::_process_payout(
+                program_id, 
+                payout_info, 
+                employer_info, 
+                employer, 
+                token_mint_info, 
+                treasury_info, 
+                token_program_info, 
+                fee,
+            )?;
+        }
+
+        let net_amount = remainder;
+        for split in shift.splits.iter().filter(|split| split.share > 0) {
+
+            // The split recipient's `salary` token account.
+            let recipient_ata_info = next_account_info(account_info_iter)?;
+            Check::ata(
+                recipient_ata_info, 
+                token_mint_info.key, 
+                &split.recipient, 
+                token_program_info.key,
+            )?;
+
+            let share = split.amount(net_amount);
+            remainder -= share;
+
+            Self::_process_payout(
+                program_id, 
+                payout_info, 
+                employer_info, 
+                employer, 
+                token_mint_info, 
+                recipient_ata_info, 
+                token_program_info, 
+                share,
+            )?;
+        }
+
+        Self::_process_payout(
+            program_id, 
+            payout_info, 
+            employer_info, 
+            employer, 
+            token_mint_info, 
+            ata_info, 
+            token_program_info, 
+            remainder,
+        )
+    }
+
+    /**
+     * Pays `amount` tokens into the `destination_info` token account.
+     * 
+     * In [BOQPayoutMode::Mint] the tokens are minted by the `mint authority` PDA account 
+     * (`payout_info`). In [BOQPayoutMode::Vault] the tokens are transferred from the employer's 
+     * vault token account (`payout_info`).
+     * 
+     * Both SPL Token and Token-2022 mints are supported. Token-2022 transfer fees are withheld 
+     * from the amount received by `destination_info`.
+     * 
+     * Throws a [ProgramError] if the vault does not hold enough tokens to pay `amount`.
+     */
+    fn _process_payout<'a>(
+        program_id: &Pubkey,
+        payout_info: &AccountInfo<'a>,
+        employer_info: &AccountInfo<'a>,
+        employer: &BOQEmployer,
+        token_mint_info: &AccountInfo<'a>,
+        destination_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        amount: u64,
+    ) -> ProgramResult {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        // The `salary` token's mint.
+        let token_mint = Self::_unpack_mint(token_mint_info)?;
+
+        match employer.payout_mode {
+
+            // Mint new tokens.
+            BOQPayoutMode::Mint => {
+
+                // The `mint authority` PDA account.
+                let mint_authority_info = payout_info;
+                Check::owner(&mint_authority_info, program_id)?;
+                let mint_authority = BOQMintAuthority::try_from_slice(
+                    &mint_authority_info.data.borrow(),
+                )?;
+                Check::initialized(&mint_authority, mint_authority_info)?;
+                let mint_seeds = &[BOQSeed::MINT_AUTHORITY.as_bytes(), &[mint_authority.bump]];
+
+                invoke_signed(
+                    &spl_token_2022::instruction::mint_to_checked(
+                        token_program_info.key, 
+                        token_mint_info.key, 
+                        destination_info.key, 
+                        mint_authority_info.key, 
+                        &[], 
+                        amount, 
+                        token_mint.decimals,
+                    )?,
+                    &[
+                        token_mint_info.clone(),
+                        destination_info.clone(),
+                        mint_authority_info.clone(),
+                        token_program_info.clone(),
+                    ],
+                    &[
+                        mint_seeds,
+                    ]
+                )
+            },
+
+            // Transfer tokens from the employer's vault.
+            BOQPayoutMode::Vault => {
+
+                // The employer's vault token account.
+                let vault_info = payout_info;
+                let token_program_id = token_program_info.key;
+                Check::ata(vault_info, token_mint_info.key, employer_info.key, token_program_id)?;
+                let vault = Self::_unpack_token_account(vault_info)?;
+                if vault.amount < amount {
+                    msg!("Vault balance {} is less than the payout {}", vault.amount, amount);
+                    return Err(BOQError::InsufficientVaultBalance.into());
+                }
+                let employer_seeds = &[BOQSeed::EMPLOYER.as_bytes(), &[employer.bump]];
+
+                invoke_signed(
+                    &spl_token_2022::instruction::transfer_checked(
+                        token_program_info.key, 
+                        vault_info.key, 
+                        token_mint_info.key, 
+                        destination_info.key, 
+                        employer_info.key, 
+                        &[], 
+                        amount, 
+                        token_mint.decimals,
+                    )?,
+                    &[
+                        vault_info.clone(),
+                        token_mint_info.clone(),
+                        destination_info.clone(),
+                        employer_info.clone(),
+                        token_program_info.clone(),
+                    ],
+                    &[
+                        employer_seeds,
+                    ]
+                )
+            },
+        }
+    }
+
+    /**
+     * Transfers `amount` tokens from the funder's token account into the employer's vault, 
+     * creating the vault if it does not exist.
+     * 
+     * `Must be signed by the funder`.
+     * 
+     * Throws a [ProgramError] if the vault is not the employer's associated token account.
+     */
+    fn process_fund_employer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+
+        // The instruction accounts.
+        let account_info_iter = &mut accounts.iter();
+
+        // The funder (transaction fee payer).
+        let funder_info = next_account_info(account_info_iter)?;
+        Check::signer(funder_info)?;
+
+        // The funder's token account.
+        let source_info = next_account_info(account_info_iter)?;
+
+        // The `employer` PDA account.
+        let employer_info = next_account_info(account_info_iter)?;
+        Check::owner(&employer_info, program_id)?;
+        let employer = BOQEmployer::try_from_slice(&employer_info.data.borrow())?;
+        Check::initialized(&employer, employer_info)?;
+
+        // The employer's vault token account.
+        let vault_info = next_account_info(account_info_iter)?;
+
+        // The `salary` token's mint account.
+        let token_mint_info = next_account_info(account_info_iter)?;
+        Check::pubkey(token_mint_info.key, &employer.token_mint)?;
+        let token_mint = Self::_unpack_mint(token_mint_info)?;
+
+        // The system program.
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        // The token program (SPL Token or Token-2022).
+        let token_program_info = next_account_info(account_info_iter)?;
+        Check::token_program(token_program_info, token_mint_info)?;
+        Check::ata(vault_info, token_mint_info.key, employer_info.key, token_program_info.key)?;
+
+        // The associated token program.
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+
+        // Creates the vault if it does not exist.
+        invoke(
+            &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                funder_info.key, 
+                employer_info.key, 
+                token_mint_info.key, 
+                token_program_info.key,
+            ), 
+            &[
+                funder_info.clone(),
+                vault_info.clone(),
+                employer_info.clone(),
+                token_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        invoke(
+            &spl_token_2022::instruction::transfer_checked(
+                token_program_info.key, 
+                source_info.key, 
+                token_mint_info.key, 
+                vault_info.key, 
+                funder_info.key, 
+                &[], 
+                amount, 
+                token_mint.decimals,
+            )?,
+            &[
+                source_info.clone(),
+                token_mint_info.clone(),
+                vault_info.clone(),
+                funder_info.clone(),
+                token_program_info.clone(),
+            ],
+        )
+    }
+
+    /**
+     * Unpacks the SPL Token or Token-2022 account `token_info`, ignoring any extensions.
+     * 
+     * Throws a [ProgramError] if the account is not owned by a token program.
+     */
+    fn _unpack_token_account(
+        token_info: &AccountInfo,
+    ) -> Result<Account, ProgramError> {
+        spl_token_2022::check_spl_token_program_account(token_info.owner)?;
+        let token_data = token_info.data.borrow();
+        Ok(StateWithExtensions::<Account>::unpack(&token_data)?.base)
+    }
+
+    /**
+     * Unpacks the SPL Token or Token-2022 mint `mint_info`, ignoring any extensions.
+     * 
+     * Throws a [ProgramError] if the account is not owned by a token program.
+     */
+    fn _unpack_mint(
+        mint_info: &AccountInfo,
+    ) -> Result<Mint, ProgramError> {
+        spl_token_2022::check_spl_token_program_account(mint_info.owner)?;
+        let mint_data = mint_info.data.borrow();
+        Ok(StateWithExtensions::<Mint>::unpack(&mint_data)?.base)
     }
 }
\ No newline at end of file
@@ -1,12 +1,16 @@
 use {
     borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
-    solana_program::{pubkey::Pubkey, slot_history::Slot},
+    solana_program::{keccak::hashv, pubkey::Pubkey, slot_history::Slot},
+    std::cmp::min,
 };
 
 /***************************************************************************************************
  * ACCOUNTS
 ***************************************************************************************************/
 
+/// The denominator of all basis point values (100%).
+pub const MAX_BASIS_POINTS: u64 = 10_000;
+
 #[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
 pub enum BOQAccountType {
     Uninitialized,
@@ -14,6 +18,10 @@ pub enum BOQAccountType {
     Employer,
     Employee,
     Shift,
+    Vesting,
+    RarityTable,
+    Schedule,
+    Rental,
 }
 
 impl Default for BOQAccountType {
@@ -22,12 +30,200 @@ impl Default for BOQAccountType {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub enum BOQRewardMode {
+    Employee,
+    Pool,
+}
+
+impl Default for BOQRewardMode {
+    fn default() -> Self {
+        BOQRewardMode::Employee
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub enum BOQPayoutMode {
+    Mint,
+    Vault,
+}
+
+impl Default for BOQPayoutMode {
+    fn default() -> Self {
+        BOQPayoutMode::Mint
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub enum BOQStakingMode {
+    None,
+    Custodial,
+    Freeze,
+}
+
+impl Default for BOQStakingMode {
+    fn default() -> Self {
+        BOQStakingMode::None
+    }
+}
+
+/// A lock-up commitment an employee can make in exchange for a multiplier on its base rate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQLockTier {
+    pub shifts: u16,
+    pub multiplier: u16,
+}
+
+impl BOQLockTier {
+
+    pub const MAX_SIZE: usize = 
+        2 +
+        2;
+}
+
+/// An attendance streak an employee can reach for a multiplier on its base rate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQStreakTier {
+    pub streak: u16,
+    pub multiplier: u16,
+}
+
+impl BOQStreakTier {
+
+    pub const MAX_SIZE: usize = 
+        2 +
+        2;
+}
+
+/// The experience (worked slots) an employee needs to reach a level, and the level's multiplier 
+/// on its base rate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQLevelTier {
+    pub slots: u64,
+    pub multiplier: u16,
+}
+
+impl BOQLevelTier {
+
+    pub const MAX_SIZE: usize = 
+        8 +
+        2;
+}
+
+/// The experience (worked slots) at which an employee is paid a one-time bonus.
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQMilestone {
+    pub slots: u64,
+    pub bonus: u64,
+}
+
+impl BOQMilestone {
+
+    pub const MAX_SIZE: usize = 
+        8 +
+        8;
+}
+
+/// A slot range in which a multiplier applies to the base rate of all employees.
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQBonusWindow {
+    pub start_slot: Slot,
+    pub end_slot: Slot,
+    pub multiplier: u16,
+}
+
+impl BOQBonusWindow {
+
+    pub const MAX_SIZE: usize = 
+        8 +
+        8 +
+        2;
+}
+
+/// A recipient of a share of a shift account's withdrawals.
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQSplit {
+    pub recipient: Pubkey,
+    pub share: u16,
+}
+
+impl BOQSplit {
+
+    pub const MAX_SIZE: usize = 
+        32 +
+        2;
+
+    /**
+     * The recipient's share of `amount` tokens.
+     */
+    pub fn amount(&self, amount: u64) -> u64 {
+        let share = u128::from(amount) * u128::from(self.share);
+        (share / u128::from(MAX_BASIS_POINTS)) as u64
+    }
+}
+
+/// A change of the base rate, recording the rate that was in effect before `slot`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQRateChange {
+    pub slot: Slot,
+    pub base_rate_per_slot: u64,
+}
+
+impl BOQRateChange {
+
+    pub const MAX_SIZE: usize = 
+        8 +
+        8;
+}
+
+/// The tokens locked by one payout, which unlock linearly from `start_slot` to `end_slot`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQVestingTranche {
+    pub start_slot: Slot,
+    pub end_slot: Slot,
+    pub amount: u64,
+    pub released: u64,
+}
+
+impl BOQVestingTranche {
+
+    pub const MAX_SIZE: usize = 
+        8 +
+        8 +
+        8 +
+        8;
+
+    /**
+     * The number of tokens that are still locked.
+     */
+    pub fn locked(&self) -> u64 {
+        self.amount - self.released
+    }
+
+    /**
+     * The number of locked tokens that have unlocked by `slot` and not been released yet.
+     */
+    pub fn releasable(&self, slot: Slot) -> u64 {
+        if slot >= self.end_slot {
+            return self.locked();
+        }
+        let elapsed_slots = slot.saturating_sub(self.start_slot);
+        let duration = self.end_slot - self.start_slot;
+        let unlocked = u128::from(self.amount) * u128::from(elapsed_slots) / u128::from(duration);
+        (unlocked as u64).saturating_sub(self.released)
+    }
+}
+
 pub struct BOQSeed;
 impl BOQSeed {
     pub const MINT_AUTHORITY: &'static str = "mint_authority";
     pub const EMPLOYER: &'static str = "employer";
     pub const EMPLOYEE: &'static str = "employee";
     pub const SHIFT: &'static str = "shift";
+    pub const VESTING: &'static str = "vesting";
+    pub const RARITY_TABLE: &'static str = "rarity_table";
+    pub const SCHEDULE: &'static str = "schedule";
+    pub const RENTAL: &'static str = "rental";
 }
 
 pub trait BOQAccount {
@@ -85,10 +281,132 @@ pub struct BOQEmployer {
     pub end_slot: Slot,
     pub slots_per_shift: u64,
     pub base_rate_per_slot: u64,
+    /// Retained from the legacy layout for off-chain readers, as the current undecayed base rate 
+    /// divided by [BOQEmployer::INFLATION_DIVISOR]. Wages use [BOQEmployer::inflation_amount].
     pub inflation_rate_per_slot: u64,
 
     pub token_mint: Pubkey,
     pub collection_mint: Pubkey,
+
+    pub version: u8,
+
+    pub shifts_per_decay: u64,
+    pub decay_rate: u16,
+
+    pub max_emission: u64,
+    pub total_emitted: u64,
+
+    pub reward_mode: BOQRewardMode,
+    pub pool_rate_per_slot: u64,
+    pub pool_reward_per_employee: u128,
+    pub pool_slot: Slot,
+
+    pub payout_mode: BOQPayoutMode,
+    pub staking_mode: BOQStakingMode,
+
+    pub vesting_rate: u16,
+    pub vesting_slots: u64,
+
+    pub lock_tiers: [BOQLockTier; BOQEmployer::MAX_LOCK_TIERS],
+
+    pub crew_size: u8,
+    pub crew_bonus_rate: u16,
+
+    pub streak_tiers: [BOQStreakTier; BOQEmployer::MAX_STREAK_TIERS],
+
+    pub level_tiers: [BOQLevelTier; BOQEmployer::MAX_LEVELS],
+    pub update_metadata: bool,
+
+    pub milestones: [BOQMilestone; BOQEmployer::MAX_MILESTONES],
+
+    pub has_schedule: bool,
+
+    pub rate_history: [BOQRateChange; BOQEmployer::MAX_RATE_CHANGES],
+
+    pub boost_cost: u64,
+    pub boost_multiplier: u16,
+    pub boost_slots: u64,
+
+    pub fee_rate: u16,
+    pub treasury: Pubkey,
+    pub total_fees: u64,
+
+    pub referral_rate: u16,
+    pub referral_shifts: u16,
+    pub max_referral_rewards: u64,
+}
+
+/// The settings of [crate::instruction::BOQInstruction::InitializeEmployer] beyond the shift 
+/// schedule and base rate. [BOQEmployerConfig::default] leaves every feature off.
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQEmployerConfig {
+
+    pub shifts_per_decay: u64,
+    pub decay_rate: u16,
+    pub max_emission: u64,
+
+    pub reward_mode: BOQRewardMode,
+    pub pool_rate_per_slot: u64,
+
+    pub payout_mode: BOQPayoutMode,
+    pub staking_mode: BOQStakingMode,
+
+    pub vesting_rate: u16,
+    pub vesting_slots: u64,
+
+    pub lock_tiers: [BOQLockTier; BOQEmployer::MAX_LOCK_TIERS],
+
+    pub crew_size: u8,
+    pub crew_bonus_rate: u16,
+
+    pub streak_tiers: [BOQStreakTier; BOQEmployer::MAX_STREAK_TIERS],
+
+    pub level_tiers: [BOQLevelTier; BOQEmployer::MAX_LEVELS],
+    pub update_metadata: bool,
+
+    pub milestones: [BOQMilestone; BOQEmployer::MAX_MILESTONES],
+
+    pub boost_cost: u64,
+    pub boost_multiplier: u16,
+    pub boost_slots: u64,
+
+    pub fee_rate: u16,
+    pub treasury: Pubkey,
+
+    pub referral_rate: u16,
+    pub referral_shifts: u16,
+    pub max_referral_rewards: u64,
+}
+
+impl Default for BOQEmployerConfig {
+    fn default() -> Self {
+        BOQEmployerConfig {
+            shifts_per_decay: 0,
+            decay_rate: 5_000,
+            max_emission: u64::MAX,
+            reward_mode: BOQRewardMode::default(),
+            pool_rate_per_slot: 100_000,
+            payout_mode: BOQPayoutMode::default(),
+            staking_mode: BOQStakingMode::default(),
+            vesting_rate: 0,
+            vesting_slots: 0,
+            lock_tiers: Default::default(),
+            crew_size: 0,
+            crew_bonus_rate: 0,
+            streak_tiers: Default::default(),
+            level_tiers: Default::default(),
+            update_metadata: false,
+            milestones: Default::default(),
+            boost_cost: 0,
+            boost_multiplier: 0,
+            boost_slots: 0,
+            fee_rate: 0,
+            treasury: Pubkey::default(),
+            referral_rate: 0,
+            referral_shifts: 0,
+            max_referral_rewards: u64::MAX,
+        }
+    }
 }
 
 impl BOQAccount for BOQEmployer {
@@ -100,7 +418,8 @@ impl BOQAccount for BOQEmployer {
 
 impl BOQEmployer {
 
-    pub const MAX_SIZE: usize = 
+    /// The size of an employer account created before `version` was added.
+    pub const LEGACY_SIZE: usize = 
         1 + 
         1 +
         1 + 
@@ -114,6 +433,65 @@ impl BOQEmployer {
         32 +
         32;
 
+    pub const MAX_SIZE: usize = 
+        BOQEmployer::LEGACY_SIZE +
+        1 +
+        8 +
+        2 +
+        8 +
+        8 +
+        1 +
+        8 +
+        16 +
+        8 +
+        1 +
+        1 +
+        2 +
+        8 +
+        (BOQLockTier::MAX_SIZE * BOQEmployer::MAX_LOCK_TIERS) +
+        1 +
+        2 +
+        (BOQStreakTier::MAX_SIZE * BOQEmployer::MAX_STREAK_TIERS) +
+        (BOQLevelTier::MAX_SIZE * BOQEmployer::MAX_LEVELS) +
+        1 +
+        (BOQMilestone::MAX_SIZE * BOQEmployer::MAX_MILESTONES) +
+        1 +
+        (BOQRateChange::MAX_SIZE * BOQEmployer::MAX_RATE_CHANGES) +
+        8 +
+        2 +
+        8 +
+        2 +
+        32 +
+        8 +
+        2 +
+        2 +
+        8;
+
+    /// The layout version of employer accounts.
+    pub const VERSION: u8 = 1;
+
+    /// The number of lock-up tiers an employer can offer.
+    pub const MAX_LOCK_TIERS: usize = 4;
+
+    /// The number of attendance streak tiers an employer can offer.
+    pub const MAX_STREAK_TIERS: usize = 4;
+
+    /// The number of levels an employee can reach.
+    pub const MAX_LEVELS: usize = 8;
+
+    /// The number of milestones an employer can offer (one bit each in 
+    /// [BOQEmployee::milestones]).
+    pub const MAX_MILESTONES: usize = 8;
+
+    /// The number of base rate changes kept in `rate_history`.
+    pub const MAX_RATE_CHANGES: usize = 8;
+
+    /// The fraction of the base rate paid as inflation per completed shift.
+    pub const INFLATION_DIVISOR: u64 = 1000;
+
+    /// The fixed-point precision of `pool_reward_per_employee`.
+    pub const POOL_PRECISION: u128 = 1_000_000_000_000;
+
     pub fn new(
         bump: u8,
         is_active: bool,
@@ -124,6 +502,7 @@ impl BOQEmployer {
         base_rate_per_slot: u64,
         token_mint: Pubkey,
         collection_mint: Pubkey,
+        config: BOQEmployerConfig,
     ) -> Self {
         Self { 
             account_type: BOQAccountType::Employer,
@@ -135,10 +514,361 @@ impl BOQEmployer {
             end_slot: start_slot + (u64::from(max_shifts) * slots_per_shift),
             slots_per_shift,
             base_rate_per_slot,
-            inflation_rate_per_slot: base_rate_per_slot / 1000,
+            inflation_rate_per_slot: base_rate_per_slot / Self::INFLATION_DIVISOR,
             token_mint,
             collection_mint,
+            version: Self::VERSION,
+            shifts_per_decay: config.shifts_per_decay,
+            decay_rate: config.decay_rate,
+            max_emission: config.max_emission,
+            total_emitted: 0,
+            reward_mode: config.reward_mode,
+            pool_rate_per_slot: config.pool_rate_per_slot,
+            pool_reward_per_employee: 0,
+            pool_slot: start_slot,
+            payout_mode: config.payout_mode,
+            staking_mode: config.staking_mode,
+            vesting_rate: config.vesting_rate,
+            vesting_slots: config.vesting_slots,
+            lock_tiers: config.lock_tiers,
+            crew_size: config.crew_size,
+            crew_bonus_rate: config.crew_bonus_rate,
+            streak_tiers: config.streak_tiers,
+            level_tiers: config.level_tiers,
+            update_metadata: config.update_metadata,
+            milestones: config.milestones,
+            has_schedule: false,
+            rate_history: Default::default(),
+            boost_cost: config.boost_cost,
+            boost_multiplier: config.boost_multiplier,
+            boost_slots: config.boost_slots,
+            fee_rate: config.fee_rate,
+            treasury: config.treasury,
+            total_fees: 0,
+            referral_rate: config.referral_rate,
+            referral_shifts: config.referral_shifts,
+            max_referral_rewards: config.max_referral_rewards,
+        }
+    }
+
+    /**
+     * Reads an employer account created before `version` was added. The settings added since 
+     * take their defaults (see [BOQEmployerConfig]).
+     */
+    pub fn from_legacy(data: &[u8]) -> Result<Self, std::io::Error> {
+        let data = &mut &data[..];
+        let account_type = BOQAccountType::deserialize(data)?;
+        let bump = u8::deserialize(data)?;
+        let is_active = bool::deserialize(data)?;
+        let employees = u16::deserialize(data)?;
+        let max_employees = u16::deserialize(data)?;
+        let start_slot = Slot::deserialize(data)?;
+        let end_slot = Slot::deserialize(data)?;
+        let slots_per_shift = u64::deserialize(data)?;
+        let base_rate_per_slot = u64::deserialize(data)?;
+        let inflation_rate_per_slot = u64::deserialize(data)?;
+        let token_mint = Pubkey::deserialize(data)?;
+        let collection_mint = Pubkey::deserialize(data)?;
+
+        let mut employer = Self::new(
+            bump, 
+            is_active, 
+            0, 
+            max_employees, 
+            start_slot, 
+            slots_per_shift, 
+            base_rate_per_slot, 
+            token_mint, 
+            collection_mint, 
+            BOQEmployerConfig::default(),
+        );
+        employer.account_type = account_type;
+        employer.employees = employees;
+        employer.end_slot = end_slot;
+        employer.inflation_rate_per_slot = inflation_rate_per_slot;
+        Ok(employer)
+    }
+
+    /**
+     * The extra amount earned from `from_slot` to `to_slot` by applying `multiplier` (in basis 
+     * points) to the base rate.
+     */
+    pub fn bonus_amount(&self, from_slot: Slot, to_slot: Slot, multiplier: u16) -> u64 {
+        let multiplier = u64::from(multiplier);
+        if multiplier <= MAX_BASIS_POINTS || to_slot <= from_slot {
+            return 0;
+        }
+        let base_amount = u128::from(self.base_amount(from_slot, to_slot));
+        let bonus = base_amount * u128::from(multiplier - MAX_BASIS_POINTS);
+        (bonus / u128::from(MAX_BASIS_POINTS)) as u64
+    }
+
+    /**
+     * The crew bonus for `employees` distinct employees that earned `amount` tokens in the same 
+     * shift, or 0 if the crew is smaller than `crew_size`.
+     */
+    pub fn crew_bonus_amount(&self, employees: u8, amount: u64) -> u64 {
+        if self.crew_size == 0 || employees < self.crew_size {
+            return 0;
+        }
+        let bonus = u128::from(amount) * u128::from(self.crew_bonus_rate);
+        (bonus / u128::from(MAX_BASIS_POINTS)) as u64
+    }
+
+    /**
+     * The index of the shift that `slot` falls in, counted from `start_slot`.
+     */
+    pub fn shift_index(&self, slot: Slot) -> u64 {
+        slot.saturating_sub(self.start_slot) / self.slots_per_shift
+    }
+
+    /**
+     * The multiplier (in basis points) of the highest streak tier reached by `streak`, or 0 if no 
+     * tier has been reached.
+     */
+    pub fn streak_multiplier(&self, streak: u16) -> u16 {
+        self.streak_tiers
+            .iter()
+            .filter(|tier| tier.streak > 0 && streak >= tier.streak)
+            .map(|tier| tier.multiplier)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /**
+     * The level reached by an employee that has worked `total_slots` slots. Levels are reached in 
+     * order, and a tier without a threshold ends the level table.
+     */
+    pub fn level(&self, total_slots: u64) -> u8 {
+        let level = self.level_tiers
+            .iter()
+            .take_while(|tier| tier.slots > 0 && total_slots >= tier.slots)
+            .count();
+        level as u8
+    }
+
+    /**
+     * The multiplier (in basis points) of `level`, or 0 for level 0.
+     */
+    pub fn level_multiplier(&self, level: u8) -> u16 {
+        match usize::from(level).checked_sub(1) {
+            Some(index) => self.level_tiers.get(index).map_or(0, |tier| tier.multiplier),
+            None => 0,
+        }
+    }
+
+    /**
+     * The referral reward for a referred shift account that earned `amount` tokens, truncated to 
+     * what is left of `max_referral_rewards` for a referrer that has earned `referral_rewards`.
+     */
+    pub fn referral_amount(&self, amount: u64, referral_rewards: u64) -> u64 {
+        let reward = u128::from(amount) * u128::from(self.referral_rate);
+        let reward = (reward / u128::from(MAX_BASIS_POINTS)) as u64;
+        min(reward, self.max_referral_rewards.saturating_sub(referral_rewards))
+    }
+
+    /**
+     * The portion of a payout of `amount` tokens that is paid to the treasury.
+     */
+    pub fn fee_amount(&self, amount: u64) -> u64 {
+        let fee = u128::from(amount) * u128::from(self.fee_rate);
+        (fee / u128::from(MAX_BASIS_POINTS)) as u64
+    }
+
+    /**
+     * The portion of a payout of `amount` tokens that is locked for vesting.
+     */
+    pub fn locked_amount(&self, amount: u64) -> u64 {
+        let locked = u128::from(amount) * u128::from(self.vesting_rate);
+        (locked / u128::from(MAX_BASIS_POINTS)) as u64
+    }
+
+    /**
+     * The number of tokens that can still be emitted before reaching `max_emission`.
+     */
+    pub fn remaining_emission(&self) -> u64 {
+        self.max_emission.saturating_sub(self.total_emitted)
+    }
+
+    /**
+     * Records the emission of `amount` tokens and deactivates the employer once `max_emission` 
+     * has been reached.
+     * 
+     * Returns the number of tokens that can be emitted, which is `amount` truncated to the 
+     * remaining emission.
+     */
+    pub fn emit(&mut self, amount: u64) -> u64 {
+        let amount = min(amount, self.remaining_emission());
+        self.total_emitted += amount;
+        if self.remaining_emission() == 0 {
+            self.is_active = false;
         }
+        amount
+    }
+
+    /**
+     * Distributes the pool's rewards for the slots elapsed since the last update equally among 
+     * all registered employees.
+     * 
+     * Slots outside of `start_slot..end_slot` and slots without any registered employees are not 
+     * distributed.
+     */
+    pub fn update_pool(&mut self, slot: Slot) {
+        if self.reward_mode != BOQRewardMode::Pool {
+            return;
+        }
+        let slot = min(slot, self.end_slot);
+        if slot > self.pool_slot {
+            if self.employees > 0 {
+                let slots = u128::from(slot - self.pool_slot);
+                let rewards = u128::from(self.pool_rate_per_slot) * slots * Self::POOL_PRECISION;
+                self.pool_reward_per_employee += rewards / u128::from(self.employees);
+            }
+            self.pool_slot = slot;
+        }
+    }
+
+    /**
+     * Returns the pool rewards accrued by `employee` since its last claim and marks them as 
+     * claimed.
+     */
+    pub fn claim_pool(&self, employee: &mut BOQEmployee) -> u64 {
+        let rewards = self.pool_reward_per_employee - employee.pool_reward_per_employee;
+        employee.pool_reward_per_employee = self.pool_reward_per_employee;
+        (rewards / Self::POOL_PRECISION) as u64
+    }
+
+    /**
+     * Starts accruing rewards for `employee` from `slot`, forfeiting any rewards accrued before.
+     */
+    pub fn clock_in(&self, employee: &mut BOQEmployee, slot: Slot) {
+        employee.last_slot = slot;
+        employee.pool_reward_per_employee = self.pool_reward_per_employee;
+    }
+
+    /**
+     * The number of slots in each decay period (0 if the base rate does not decay).
+     */
+    pub fn slots_per_decay(&self) -> u64 {
+        self.shifts_per_decay * self.slots_per_shift
+    }
+
+    /**
+     * `base_rate_per_slot` after `periods` decay periods.
+     * 
+     * The rate is multiplied by `decay_rate` (in basis points) once per period, e.g. a decay rate 
+     * of 5,000 halves the base rate every period.
+     */
+    pub fn decayed_rate_per_slot(&self, base_rate_per_slot: u64, periods: u64) -> u64 {
+        // Fixed-point scale of the decay factor.
+        const SCALE: u128 = 1_000_000_000_000;
+        let mut rate = u128::from(base_rate_per_slot);
+        let mut factor = u128::from(self.decay_rate) * (SCALE / u128::from(MAX_BASIS_POINTS));
+        let mut exponent = periods;
+        while exponent > 0 && rate > 0 {
+            if exponent & 1 == 1 {
+                rate = rate * factor / SCALE;
+            }
+            factor = factor * factor / SCALE;
+            exponent >>= 1;
+        }
+        rate as u64
+    }
+
+    /**
+     * Returns the base rate per slot (before decay) in effect at `slot`, and the slot at which it 
+     * was replaced ([Slot::MAX] for the current rate).
+     */
+    pub fn base_rate_at(&self, slot: Slot) -> (u64, Slot) {
+        self.rate_history
+            .iter()
+            .filter(|change| change.slot > slot)
+            .min_by_key(|change| change.slot)
+            .map_or((self.base_rate_per_slot, Slot::MAX), |change| {
+                (change.base_rate_per_slot, change.slot)
+            })
+    }
+
+    /**
+     * Sets `base_rate_per_slot` as the base rate from `slot` onwards, recording the previous rate 
+     * in `rate_history` so that slots before `slot` are still paid at the previous rate.
+     * 
+     * `rate_history` is a ring buffer of the latest changes, not a full history. A full buffer 
+     * replaces its oldest change, which is only allowed once no employee can be paid for slots 
+     * before it (see [BOQEmployer::base_amount]). The inflation rate is derived from the base 
+     * rate (see [BOQEmployer::inflation_amount]), so it changes with it.
+     * 
+     * Returns false if the buffer is full of changes that are still needed.
+     */
+    pub fn set_base_rate(&mut self, base_rate_per_slot: u64, slot: Slot) -> bool {
+        if self.rate_history.iter().any(|change| change.slot == slot) {
+            self.base_rate_per_slot = base_rate_per_slot;
+            self.inflation_rate_per_slot = base_rate_per_slot / Self::INFLATION_DIVISOR;
+            return true;
+        }
+        let oldest_change = self.rate_history
+            .iter_mut()
+            .min_by_key(|change| change.slot)
+            .unwrap();
+        let is_expired = oldest_change.slot.saturating_add(self.slots_per_shift) <= slot;
+        if oldest_change.slot != 0 && !is_expired {
+            return false;
+        }
+        *oldest_change = BOQRateChange { slot, base_rate_per_slot: self.base_rate_per_slot };
+        self.base_rate_per_slot = base_rate_per_slot;
+        self.inflation_rate_per_slot = base_rate_per_slot / Self::INFLATION_DIVISOR;
+        true
+    }
+
+    /**
+     * The base amount earned for working the slots `from_slot..to_slot`.
+     * 
+     * An interval that crosses one or more base rate changes is split, so that each part is paid 
+     * at the rate in effect for it. Intervals are never longer than `slots_per_shift`, so only the 
+     * changes of the last shift are needed.
+     */
+    pub fn base_amount(&self, from_slot: Slot, to_slot: Slot) -> u64 {
+        let mut amount = 0;
+        let mut slot = from_slot;
+        while slot < to_slot {
+            let (base_rate_per_slot, change_slot) = self.base_rate_at(slot);
+            let next_slot = min(change_slot, to_slot);
+            amount += self.decayed_amount(base_rate_per_slot, slot, next_slot);
+            slot = next_slot;
+        }
+        amount
+    }
+
+    /**
+     * The inflation earned for working the slots `from_slot..to_slot`, per completed shift of the 
+     * employee. The inflation rate is a fixed fraction of the base rate in effect, so it follows 
+     * the base rate's decay and changes.
+     */
+    pub fn inflation_amount(&self, from_slot: Slot, to_slot: Slot) -> u64 {
+        self.base_amount(from_slot, to_slot) / Self::INFLATION_DIVISOR
+    }
+
+    /**
+     * The amount earned at `rate_per_slot` (before decay) for the slots `from_slot..to_slot`.
+     * 
+     * An interval that crosses one or more decay boundaries is split, so that each part is paid 
+     * at the rate decayed for its period.
+     */
+    pub fn decayed_amount(&self, rate_per_slot: u64, from_slot: Slot, to_slot: Slot) -> u64 {
+        let slots_per_decay = self.slots_per_decay();
+        if slots_per_decay == 0 {
+            return rate_per_slot * to_slot.saturating_sub(from_slot);
+        }
+        let mut amount = 0;
+        let mut slot = from_slot;
+        while slot < to_slot {
+            let period = slot.saturating_sub(self.start_slot) / slots_per_decay;
+            let boundary = self.start_slot + ((period + 1) * slots_per_decay);
+            let next_slot = min(boundary, to_slot);
+            amount += self.decayed_rate_per_slot(rate_per_slot, period) * (next_slot - slot);
+            slot = next_slot;
+        }
+        amount
     }
 
     // pub fn current_shift(&self, slot: Slot) -> u64 {
@@ -162,6 +892,22 @@ pub struct BOQEmployee {
     pub last_slot: Slot,
     pub total_slots: Slot,
     pub nft_mint: Pubkey,
+    pub version: u8,
+    pub pool_reward_per_employee: u128,
+    pub is_staked: bool,
+    pub staker: Pubkey,
+    pub holder: Pubkey,
+    pub lock_start_slot: Slot,
+    pub lock_end_slot: Slot,
+    pub lock_multiplier: u16,
+    pub rarity_multiplier: u16,
+    pub streak: u16,
+    pub level: u8,
+    pub milestones: u8,
+    pub boost_start_slot: Slot,
+    pub boost_end_slot: Slot,
+    pub boost_multiplier: u16,
+    pub has_rental: bool,
 }
 
 impl BOQAccount for BOQEmployee {
@@ -173,16 +919,40 @@ impl BOQAccount for BOQEmployee {
 
 impl BOQEmployee {
 
-    pub const MAX_SIZE: usize = 
+    /// The size of an employee account created before `version` was added.
+    pub const LEGACY_SIZE: usize = 
         1 + 
         1 +
         8 +
         8 +
         32;
 
+    pub const MAX_SIZE: usize = 
+        BOQEmployee::LEGACY_SIZE +
+        1 +
+        16 +
+        1 +
+        32 +
+        32 +
+        8 +
+        8 +
+        2 +
+        2 +
+        2 +
+        1 +
+        1 +
+        8 +
+        8 +
+        2 +
+        1;
+
+    /// The layout version of employee accounts.
+    pub const VERSION: u8 = 1;
+
     pub fn new(
         bump: u8,
         mint: Pubkey,
+        pool_reward_per_employee: u128,
     ) -> Self {
         Self { 
             account_type: BOQAccountType::Employee,
@@ -190,7 +960,118 @@ impl BOQEmployee {
             last_slot: 0,
             total_slots: 0,
             nft_mint: mint,
+            version: Self::VERSION,
+            pool_reward_per_employee,
+            is_staked: false,
+            staker: Pubkey::default(),
+            holder: Pubkey::default(),
+            lock_start_slot: 0,
+            lock_end_slot: 0,
+            lock_multiplier: 0,
+            rarity_multiplier: 0,
+            streak: 0,
+            level: 0,
+            milestones: 0,
+            boost_start_slot: 0,
+            boost_end_slot: 0,
+            boost_multiplier: 0,
+            has_rental: false,
+        }
+    }
+
+    /**
+     * Reads an employee account created before `version` was added.
+     */
+    pub fn from_legacy(data: &[u8]) -> Result<Self, std::io::Error> {
+        let data = &mut &data[..];
+        Ok(Self {
+            account_type: BorshDeserialize::deserialize(data)?,
+            bump: BorshDeserialize::deserialize(data)?,
+            last_slot: BorshDeserialize::deserialize(data)?,
+            total_slots: BorshDeserialize::deserialize(data)?,
+            nft_mint: BorshDeserialize::deserialize(data)?,
+            version: Self::VERSION,
+            ..Default::default()
+        })
+    }
+
+    /**
+     * Returns true if the employee is committed to a lock-up at `slot`.
+     */
+    pub fn is_locked(&self, slot: Slot) -> bool {
+        slot < self.lock_end_slot
+    }
+
+    /**
+     * The extra amount earned from `from_slot` to `to_slot` by the lock-up multiplier, which only 
+     * applies to slots within the lock-up period.
+     */
+    pub fn lock_bonus_amount(&self, employer: &BOQEmployer, from_slot: Slot, to_slot: Slot) -> u64 {
+        let from_slot = from_slot.max(self.lock_start_slot);
+        let to_slot = min(to_slot, self.lock_end_slot);
+        employer.bonus_amount(from_slot, to_slot, self.lock_multiplier)
+    }
+
+    /**
+     * Grants the employer's boost from `slot`, or extends the current boost if it is still 
+     * running at `slot`.
+     */
+    pub fn boost(&mut self, employer: &BOQEmployer, slot: Slot) {
+        if slot < self.boost_end_slot {
+            self.boost_end_slot += employer.boost_slots;
+        } else {
+            self.boost_start_slot = slot;
+            self.boost_end_slot = slot + employer.boost_slots;
         }
+        self.boost_multiplier = employer.boost_multiplier;
+    }
+
+    /**
+     * The extra amount earned from `from_slot` to `to_slot` by the boost multiplier, which only 
+     * applies to slots within the boost window.
+     */
+    pub fn boost_bonus_amount(
+        &self, 
+        employer: &BOQEmployer, 
+        from_slot: Slot, 
+        to_slot: Slot,
+    ) -> u64 {
+        let from_slot = from_slot.max(self.boost_start_slot);
+        let to_slot = min(to_slot, self.boost_end_slot);
+        employer.bonus_amount(from_slot, to_slot, self.boost_multiplier)
+    }
+
+    /**
+     * Updates the attendance streak for working at `slot`. The streak grows when the employee 
+     * works in the shift after the one it last worked in, and restarts when a shift is missed.
+     */
+    pub fn update_streak(&mut self, employer: &BOQEmployer, slot: Slot) {
+        let last_shift = employer.shift_index(self.last_slot);
+        let shift = employer.shift_index(slot);
+        if self.streak == 0 || shift > last_shift + 1 {
+            self.streak = 1;
+        } else if shift == last_shift + 1 {
+            self.streak = self.streak.saturating_add(1);
+        }
+    }
+
+    /**
+     * Grants the employer's milestones reached by `total_slots` that have not been granted yet, 
+     * recording them in the `milestones` bitmap.
+     * 
+     * Returns the total bonus of the newly granted milestones.
+     */
+    pub fn grant_milestones(&mut self, employer: &BOQEmployer) -> u64 {
+        let mut bonus = 0;
+        for (index, milestone) in employer.milestones.iter().enumerate() {
+            let bit = 1 << index;
+            let is_reached = milestone.slots > 0 && self.total_slots >= milestone.slots;
+            if is_reached && self.milestones & bit == 0 {
+                self.milestones |= bit;
+                bonus += milestone.bonus;
+            }
+        }
+        bonus
     }
 
     // pub fn total_shifts(&self, employer: &BOQEmployer) -> u64 {
@@ -211,6 +1092,14 @@ pub struct BOQShift {
     pub total_slots: u64,
     pub total_rewards: u64,
     pub owner: Pubkey,
+    pub version: u8,
+    pub claimable: u64,
+    pub total_locked: u64,
+    pub total_vested: u64,
+    pub splits: [BOQSplit; BOQShift::MAX_SPLITS],
+    pub referrer: Pubkey,
+    pub referral_start_slot: Slot,
+    pub referral_rewards: u64,
 }
 
 impl BOQAccount for BOQShift {
@@ -222,7 +1111,8 @@ impl BOQAccount for BOQShift {
 
 impl BOQShift {
 
-    pub const MAX_SIZE: usize = 
+    /// The size of a shift account created before `version` was added.
+    pub const LEGACY_SIZE: usize = 
         1 + 
         1 +
         8 +
@@ -230,6 +1120,23 @@ impl BOQShift {
         8 +
         32;
 
+    pub const MAX_SIZE: usize = 
+        BOQShift::LEGACY_SIZE +
+        1 +
+        8 +
+        8 +
+        8 +
+        (BOQSplit::MAX_SIZE * BOQShift::MAX_SPLITS) +
+        32 +
+        8 +
+        8;
+
+    /// The layout version of shift accounts.
+    pub const VERSION: u8 = 1;
+
+    /// The number of recipients in a split table.
+    pub const MAX_SPLITS: usize = 4;
+
     pub fn new(
         bump: u8, 
         slot: Slot,
@@ -242,6 +1149,729 @@ impl BOQShift {
             total_slots: 0,
             total_rewards: 0,
             owner,
+            version: Self::VERSION,
+            claimable: 0,
+            total_locked: 0,
+            total_vested: 0,
+            splits: Default::default(),
+            referrer: Pubkey::default(),
+            referral_start_slot: 0,
+            referral_rewards: 0,
+        }
+    }
+
+    /**
+     * Reads a shift account created before `version` was added.
+     */
+    pub fn from_legacy(data: &[u8]) -> Result<Self, std::io::Error> {
+        let data = &mut &data[..];
+        Ok(Self {
+            account_type: BorshDeserialize::deserialize(data)?,
+            bump: BorshDeserialize::deserialize(data)?,
+            slot: BorshDeserialize::deserialize(data)?,
+            total_slots: BorshDeserialize::deserialize(data)?,
+            total_rewards: BorshDeserialize::deserialize(data)?,
+            owner: BorshDeserialize::deserialize(data)?,
+            version: Self::VERSION,
+            ..Default::default()
+        })
+    }
+
+    /**
+     * True if the shift account's earnings at `slot` still pay a reward to its referrer.
+     * 
+     * The referral period lasts the employer's `referral_shifts` shifts of `slots_per_shift` 
+     * slots from `referral_start_slot`, the slot of the first rewarded earnings, however often 
+     * the shift account is paid.
+     */
+    pub fn is_referred(&self, employer: &BOQEmployer, slot: Slot) -> bool {
+        let referral_slots = u64::from(employer.referral_shifts) * employer.slots_per_shift;
+        self.referrer != Pubkey::default() 
+            && employer.referral_rate > 0 
+            && referral_slots > 0
+            && (self.referral_start_slot == 0 || slot < self.referral_start_slot + referral_slots)
+    }
+}
+
+/***************************************************************************************************
+ * VESTING
+***************************************************************************************************/
+
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQVesting {
+    pub account_type: BOQAccountType,
+    pub bump: u8,
+    pub tranches: [BOQVestingTranche; BOQVesting::MAX_TRANCHES],
+    pub owner: Pubkey,
+}
+
+impl BOQAccount for BOQVesting {
+
+    fn is_initialized(&self) -> bool { 
+        self.account_type == BOQAccountType::Vesting 
+    }
+}
+
+impl BOQVesting {
+
+    pub const MAX_SIZE: usize = 
+        1 + 
+        1 +
+        (BOQVestingTranche::MAX_SIZE * BOQVesting::MAX_TRANCHES) +
+        32;
+
+    /// The number of payouts that can be vesting at the same time.
+    pub const MAX_TRANCHES: usize = 8;
+
+    pub fn new(
+        bump: u8, 
+        owner: Pubkey,
+    ) -> Self {
+        Self { 
+            account_type: BOQAccountType::Vesting,
+            bump,
+            tranches: Default::default(),
+            owner,
+        }
+    }
+
+    /**
+     * The number of tokens that are still locked.
+     */
+    pub fn locked(&self) -> u64 {
+        self.tranches.iter().map(|tranche| tranche.locked()).sum()
+    }
+
+    /**
+     * The number of locked tokens that have unlocked by `slot` and not been released yet.
+     */
+    pub fn releasable(&self, slot: Slot) -> u64 {
+        self.tranches.iter().map(|tranche| tranche.releasable(slot)).sum()
+    }
+
+    /**
+     * Releases the tokens that have unlocked by `slot`.
+     * 
+     * Returns the number of tokens released.
+     */
+    pub fn release(&mut self, slot: Slot) -> u64 {
+        let mut amount = 0;
+        for tranche in self.tranches.iter_mut() {
+            let releasable = tranche.releasable(slot);
+            tranche.released += releasable;
+            amount += releasable;
+        }
+        amount
+    }
+
+    /**
+     * Locks `amount` more tokens in a tranche of their own, which unlocks linearly from `slot` 
+     * over `slots` slots. Tokens locked earlier keep their own schedule.
+     * 
+     * Tranches are reused once all of their tokens have been released, so tokens that have 
+     * unlocked by `slot` should be released first.
+     * 
+     * Returns false if every tranche still holds locked tokens.
+     */
+    pub fn lock(&mut self, amount: u64, slot: Slot, slots: u64) -> bool {
+        if amount == 0 {
+            return true;
+        }
+        match self.tranches.iter_mut().find(|tranche| tranche.locked() == 0) {
+            Some(tranche) => {
+                *tranche = BOQVestingTranche {
+                    start_slot: slot,
+                    end_slot: slot + slots,
+                    amount,
+                    released: 0,
+                };
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+/***************************************************************************************************
+ * RARITY TABLE
+***************************************************************************************************/
+
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQRarityTable {
+    pub account_type: BOQAccountType,
+    pub bump: u8,
+    pub merkle_root: [u8; 32],
+}
+
+impl BOQAccount for BOQRarityTable {
+
+    fn is_initialized(&self) -> bool { 
+        self.account_type == BOQAccountType::RarityTable 
+    }
+}
+
+impl BOQRarityTable {
+
+    pub const MAX_SIZE: usize = 
+        1 + 
+        1 +
+        32;
+
+    pub fn new(
+        bump: u8, 
+        merkle_root: [u8; 32],
+    ) -> Self {
+        Self { 
+            account_type: BOQAccountType::RarityTable,
+            bump,
+            merkle_root,
+        }
+    }
+
+    /**
+     * The merkle leaf assigning `multiplier` (in basis points) to the NFT `nft_mint`.
+     */
+    pub fn leaf(nft_mint: &Pubkey, multiplier: u16) -> [u8; 32] {
+        hashv(&[nft_mint.as_ref(), &multiplier.to_le_bytes()]).to_bytes()
+    }
+}
+
+/***************************************************************************************************
+ * SCHEDULE
+***************************************************************************************************/
+
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQSchedule {
+    pub account_type: BOQAccountType,
+    pub bump: u8,
+    pub windows: [BOQBonusWindow; BOQSchedule::MAX_WINDOWS],
+}
+
+impl BOQAccount for BOQSchedule {
+
+    fn is_initialized(&self) -> bool { 
+        self.account_type == BOQAccountType::Schedule 
+    }
+}
+
+impl BOQSchedule {
+
+    pub const MAX_SIZE: usize = 
+        1 + 
+        1 +
+        (BOQBonusWindow::MAX_SIZE * BOQSchedule::MAX_WINDOWS);
+
+    /// The number of bonus windows that can be scheduled at once.
+    pub const MAX_WINDOWS: usize = 8;
+
+    pub fn new(bump: u8) -> Self {
+        Self { 
+            account_type: BOQAccountType::Schedule,
+            bump,
+            windows: Default::default(),
+        }
+    }
+
+    /**
+     * The extra amount earned from `from_slot` to `to_slot` by the bonus windows. The interval is 
+     * split at the boundaries of each window, and each window's multiplier only applies to the 
+     * part of the interval it overlaps.
+     */
+    pub fn bonus_amount(&self, employer: &BOQEmployer, from_slot: Slot, to_slot: Slot) -> u64 {
+        self.windows
+            .iter()
+            .map(|window| {
+                let from_slot = from_slot.max(window.start_slot);
+                let to_slot = min(to_slot, window.end_slot);
+                employer.bonus_amount(from_slot, to_slot, window.multiplier)
+            })
+            .sum()
+    }
+}
+
+/***************************************************************************************************
+ * RENTAL
+***************************************************************************************************/
+
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct BOQRental {
+    pub account_type: BOQAccountType,
+    pub bump: u8,
+    pub owner: Pubkey,
+    pub operator: Pubkey,
+    pub start_slot: Slot,
+    pub end_slot: Slot,
+    pub owner_share: u16,
+    pub employee: Pubkey,
+}
+
+impl BOQAccount for BOQRental {
+
+    fn is_initialized(&self) -> bool { 
+        self.account_type == BOQAccountType::Rental 
+    }
+}
+
+impl BOQRental {
+
+    pub const MAX_SIZE: usize = 
+        1 + 
+        1 +
+        32 +
+        32 +
+        8 +
+        8 +
+        2 +
+        32;
+
+    pub fn new(
+        bump: u8, 
+        employee: Pubkey,
+    ) -> Self {
+        Self { 
+            account_type: BOQAccountType::Rental,
+            bump,
+            owner: Pubkey::default(),
+            operator: Pubkey::default(),
+            start_slot: 0,
+            end_slot: 0,
+            owner_share: 0,
+            employee,
         }
     }
-}
\ No newline at end of file
+
+    /**
+     * True if the operator works the employee at `slot`.
+     */
+    pub fn is_active(&self, slot: Slot) -> bool {
+        slot >= self.start_slot && slot < self.end_slot
+    }
+
+    /**
+     * The owner's share of `amount` tokens earned by the operator.
+     */
+    pub fn owner_amount(&self, amount: u64) -> u64 {
+        let share = u128::from(amount) * u128::from(self.owner_share);
+        (share / u128::from(MAX_BASIS_POINTS)) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn employer() -> BOQEmployer {
+        BOQEmployer {
+            account_type: BOQAccountType::Employer,
+            is_active: true,
+            start_slot: 1_000,
+            end_slot: Slot::MAX,
+            slots_per_shift: 100,
+            base_rate_per_slot: 1_000,
+            decay_rate: 5_000,
+            max_emission: u64::MAX,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn decayed_rate_per_slot_applies_decay_once_per_period() {
+        let employer = employer();
+        assert_eq!(employer.decayed_rate_per_slot(1_000, 0), 1_000);
+        assert_eq!(employer.decayed_rate_per_slot(1_000, 1), 500);
+        assert_eq!(employer.decayed_rate_per_slot(1_000, 3), 125);
+        assert_eq!(employer.decayed_rate_per_slot(1_000, 64), 0);
+    }
+
+    #[test]
+    fn base_amount_without_decay() {
+        let employer = employer();
+        assert_eq!(employer.base_amount(1_000, 1_100), 100_000);
+        assert_eq!(employer.base_amount(1_100, 1_000), 0);
+    }
+
+    #[test]
+    fn base_amount_splits_at_decay_boundaries() {
+        let mut employer = employer();
+        employer.shifts_per_decay = 1;
+        assert_eq!(employer.base_amount(1_000, 1_100), 100_000);
+        assert_eq!(employer.base_amount(1_050, 1_150), 50 * 1_000 + 50 * 500);
+        assert_eq!(employer.base_amount(1_150, 1_250), 50 * 500 + 50 * 250);
+    }
+
+    #[test]
+    fn inflation_amount_decays_with_the_base_rate() {
+        let mut employer = employer();
+        employer.shifts_per_decay = 1;
+        assert_eq!(employer.inflation_amount(1_050, 1_150), (50 * 1_000 + 50 * 500) / 1_000);
+    }
+
+    #[test]
+    fn emit_truncates_to_the_remaining_emission() {
+        let mut employer = employer();
+        employer.max_emission = 1_000;
+        assert_eq!(employer.emit(600), 600);
+        assert!(employer.is_active);
+        assert_eq!(employer.emit(600), 400);
+        assert_eq!(employer.total_emitted, 1_000);
+        assert!(!employer.is_active);
+        assert_eq!(employer.emit(1), 0);
+    }
+
+    #[test]
+    fn locked_amount_is_the_vesting_rate_of_the_payout() {
+        let mut employer = employer();
+        employer.vesting_rate = 2_500;
+        assert_eq!(employer.locked_amount(1_000), 250);
+        assert_eq!(employer.locked_amount(3), 0);
+    }
+
+    #[test]
+    fn vesting_releases_linearly_until_the_end_slot() {
+        let mut vesting = BOQVesting::new(0, Pubkey::default());
+        vesting.lock(1_000, 100, 100);
+        assert_eq!(vesting.releasable(100), 0);
+        assert_eq!(vesting.release(150), 500);
+        assert_eq!(vesting.release(150), 0);
+        assert_eq!(vesting.locked(), 500);
+        assert_eq!(vesting.release(175), 250);
+        assert_eq!(vesting.release(300), 250);
+        assert_eq!(vesting.locked(), 0);
+    }
+
+    #[test]
+    fn vesting_lock_does_not_delay_tokens_locked_earlier() {
+        let mut vesting = BOQVesting::new(0, Pubkey::default());
+        assert!(vesting.lock(1_000, 100, 100));
+        assert_eq!(vesting.release(150), 500);
+        assert!(vesting.lock(500, 150, 100));
+        assert_eq!(vesting.locked(), 1_000);
+        assert_eq!(vesting.releasable(175), 250 + 125);
+        assert_eq!(vesting.releasable(200), 500 + 250);
+        assert_eq!(vesting.releasable(250), 1_000);
+    }
+
+    #[test]
+    fn vesting_lock_reuses_released_tranches_only() {
+        let mut vesting = BOQVesting::new(0, Pubkey::default());
+        for index in 0..BOQVesting::MAX_TRANCHES as u64 {
+            assert!(vesting.lock(100, 100 + index, 100));
+        }
+        assert!(!vesting.lock(100, 150, 100));
+        assert!(vesting.lock(0, 150, 100));
+        assert_eq!(vesting.release(200), 100 + 99 + 98 + 97 + 96 + 95 + 94 + 93);
+        assert!(vesting.lock(100, 200, 100));
+        assert!(!vesting.lock(100, 200, 100));
+    }
+
+    fn schedule(windows: &[(Slot, Slot, u16)]) -> BOQSchedule {
+        let mut schedule = BOQSchedule::new(0);
+        for (index, &(start_slot, end_slot, multiplier)) in windows.iter().enumerate() {
+            schedule.windows[index] = BOQBonusWindow { start_slot, end_slot, multiplier };
+        }
+        schedule
+    }
+
+    #[test]
+    fn schedule_bonus_amount_outside_all_windows() {
+        let employer = employer();
+        let schedule = schedule(&[(1_200, 1_300, 20_000)]);
+        assert_eq!(schedule.bonus_amount(&employer, 1_000, 1_100), 0);
+        assert_eq!(schedule.bonus_amount(&employer, 1_100, 1_200), 0);
+        assert_eq!(schedule.bonus_amount(&employer, 1_300, 1_400), 0);
+    }
+
+    #[test]
+    fn schedule_bonus_amount_within_one_window() {
+        let employer = employer();
+        let schedule = schedule(&[(1_000, 1_100, 15_000)]);
+        assert_eq!(schedule.bonus_amount(&employer, 1_000, 1_100), 50_000);
+        assert_eq!(schedule.bonus_amount(&employer, 1_020, 1_040), 10_000);
+    }
+
+    #[test]
+    fn schedule_bonus_amount_at_partial_window_edges() {
+        let employer = employer();
+        let schedule = schedule(&[(1_050, 1_150, 20_000)]);
+        assert_eq!(schedule.bonus_amount(&employer, 1_000, 1_100), 50_000);
+        assert_eq!(schedule.bonus_amount(&employer, 1_100, 1_200), 50_000);
+        assert_eq!(schedule.bonus_amount(&employer, 1_149, 1_151), 1_000);
+    }
+
+    #[test]
+    fn schedule_bonus_amount_across_several_windows() {
+        let employer = employer();
+        let schedule = schedule(&[
+            (1_000, 1_040, 20_000),
+            (1_060, 1_080, 15_000),
+            (1_070, 1_200, 11_000),
+        ]);
+        let amount = 40 * 1_000 + 20 * 500 + 30 * 100;
+        assert_eq!(schedule.bonus_amount(&employer, 1_000, 1_100), amount);
+    }
+
+    #[test]
+    fn base_rate_changes_are_not_retroactive() {
+        let mut employer = employer();
+        assert!(employer.set_base_rate(2_000, 1_050));
+        assert_eq!(employer.inflation_rate_per_slot, 2);
+        assert_eq!(employer.base_amount(1_000, 1_100), 50 * 1_000 + 50 * 2_000);
+        assert_eq!(employer.inflation_amount(1_000, 1_100), 150);
+        assert_eq!(employer.base_amount(1_100, 1_200), 100 * 2_000);
+    }
+
+    #[test]
+    fn rate_history_only_replaces_expired_changes() {
+        let mut employer = employer();
+        for index in 0..BOQEmployer::MAX_RATE_CHANGES as u64 {
+            assert!(employer.set_base_rate(1_000 + index, 1_001 + index));
+        }
+        assert!(!employer.set_base_rate(5_000, 1_050));
+        assert!(employer.set_base_rate(5_000, 1_101));
+        assert_eq!(employer.base_rate_at(1_001), (1_000, 1_002));
+    }
+
+    #[test]
+    fn fee_amount_rounds_down() {
+        let mut employer = employer();
+        employer.fee_rate = 250;
+        assert_eq!(employer.fee_amount(1_000), 25);
+        assert_eq!(employer.fee_amount(39), 0);
+        assert_eq!(employer.fee_amount(u64::MAX), u64::MAX / 40);
+    }
+
+    #[test]
+    fn split_amounts_round_down_leaving_the_remainder_to_the_owner() {
+        let splits = [
+            BOQSplit { recipient: Pubkey::new_unique(), share: 3_333 },
+            BOQSplit { recipient: Pubkey::new_unique(), share: 3_333 },
+        ];
+        let amounts: Vec<u64> = splits.iter().map(|split| split.amount(100)).collect();
+        assert_eq!(amounts, vec![33, 33]);
+        assert_eq!(100 - amounts.iter().sum::<u64>(), 34);
+        assert_eq!(splits[0].amount(2), 0);
+    }
+
+    #[test]
+    fn rental_owner_share_rounds_down_in_favour_of_the_operator() {
+        let mut rental = BOQRental::new(255, Pubkey::new_unique());
+        rental.start_slot = 1_000;
+        rental.end_slot = 2_000;
+        rental.owner_share = 2_500;
+        assert!(!rental.is_active(999));
+        assert!(rental.is_active(1_000));
+        assert!(!rental.is_active(2_000));
+        assert_eq!(rental.owner_amount(1_000), 250);
+        assert_eq!(rental.owner_amount(3), 0);
+        assert_eq!(rental.owner_amount(u64::MAX), u64::MAX / 4);
+    }
+
+    #[test]
+    fn legacy_employer_keeps_its_prefix_and_takes_the_initialize_defaults() {
+        let mut legacy = employer().try_to_vec().unwrap();
+        legacy.truncate(BOQEmployer::LEGACY_SIZE);
+        let employer = BOQEmployer::from_legacy(&legacy).unwrap();
+        assert_eq!(employer.try_to_vec().unwrap().len(), BOQEmployer::MAX_SIZE);
+        assert_eq!(employer.try_to_vec().unwrap()[..BOQEmployer::LEGACY_SIZE], legacy[..]);
+        assert_eq!(employer.version, BOQEmployer::VERSION);
+        assert_eq!(employer.shifts_per_decay, 0);
+        assert_eq!(employer.max_emission, u64::MAX);
+        assert_eq!(employer.pool_slot, employer.start_slot);
+        assert_eq!(employer.base_amount(1_000, 1_100), 100 * 1_000);
+    }
+
+    #[test]
+    fn legacy_employee_and_shift_keep_their_prefix() {
+        let mut legacy = BOQEmployee::new(255, Pubkey::new_unique(), 0).try_to_vec().unwrap();
+        legacy.truncate(BOQEmployee::LEGACY_SIZE);
+        let employee = BOQEmployee::from_legacy(&legacy).unwrap();
+        assert_eq!(employee.try_to_vec().unwrap().len(), BOQEmployee::MAX_SIZE);
+        assert_eq!(employee.try_to_vec().unwrap()[..BOQEmployee::LEGACY_SIZE], legacy[..]);
+
+        let mut legacy = BOQShift::new(255, 1_000, Pubkey::new_unique()).try_to_vec().unwrap();
+        legacy.truncate(BOQShift::LEGACY_SIZE);
+        let shift = BOQShift::from_legacy(&legacy).unwrap();
+        assert_eq!(shift.try_to_vec().unwrap().len(), BOQShift::MAX_SIZE);
+        assert_eq!(shift.try_to_vec().unwrap()[..BOQShift::LEGACY_SIZE], legacy[..]);
+        assert_eq!(shift.version, BOQShift::VERSION);
+    }
+
+    #[test]
+    fn referral_period_counts_employer_shifts_from_the_first_reward() {
+        let mut employer = employer();
+        employer.referral_rate = 1_000;
+        employer.referral_shifts = 2;
+        let mut shift = BOQShift::new(255, 1_000, Pubkey::new_unique());
+        assert!(!shift.is_referred(&employer, 1_000));
+        shift.referrer = Pubkey::new_unique();
+        assert!(shift.is_referred(&employer, 5_000));
+        shift.referral_start_slot = 1_050;
+        assert!(shift.is_referred(&employer, 1_249));
+        assert!(!shift.is_referred(&employer, 1_250));
+    }
+
+    #[test]
+    fn referral_amount_is_capped_by_the_referrer_rewards_left() {
+        let mut employer = employer();
+        employer.referral_rate = 1_000;
+        employer.max_referral_rewards = 250;
+        assert_eq!(employer.referral_amount(1_009, 0), 100);
+        assert_eq!(employer.referral_amount(1_000, 200), 50);
+        assert_eq!(employer.referral_amount(1_000, 300), 0);
+    }
+
+    fn pool_employer(employees: u16) -> BOQEmployer {
+        BOQEmployer {
+            reward_mode: BOQRewardMode::Pool,
+            employees,
+            end_slot: 2_000,
+            pool_rate_per_slot: 100,
+            pool_slot: 1_000,
+            ..employer()
+        }
+    }
+
+    #[test]
+    fn pool_splits_each_period_among_the_employees_registered_in_it() {
+        let mut employer = pool_employer(2);
+        let mut first = BOQEmployee::default();
+        let mut second = BOQEmployee::default();
+        employer.clock_in(&mut first, 1_000);
+        employer.clock_in(&mut second, 1_000);
+        employer.update_pool(1_100);
+
+        let mut third = BOQEmployee::default();
+        employer.employees += 1;
+        employer.clock_in(&mut third, 1_100);
+        employer.update_pool(1_200);
+
+        assert_eq!(employer.claim_pool(&mut first), 5_000 + 3_333);
+        assert_eq!(employer.claim_pool(&mut first), 0);
+        assert_eq!(employer.claim_pool(&mut second), 5_000 + 3_333);
+        assert_eq!(employer.claim_pool(&mut third), 3_333);
+    }
+
+    #[test]
+    fn pool_stops_distributing_at_the_end_slot() {
+        let mut employer = pool_employer(1);
+        let mut employee = BOQEmployee::default();
+        employer.clock_in(&mut employee, 1_000);
+        employer.update_pool(2_500);
+        assert_eq!(employer.pool_slot, 2_000);
+        employer.update_pool(3_000);
+        assert_eq!(employer.claim_pool(&mut employee), 1_000 * 100);
+    }
+
+    #[test]
+    fn pool_does_not_carry_over_periods_without_employees() {
+        let mut employer = pool_employer(0);
+        employer.update_pool(1_100);
+        assert_eq!(employer.pool_reward_per_employee, 0);
+        assert_eq!(employer.pool_slot, 1_100);
+
+        let mut employee = BOQEmployee::default();
+        employer.employees = 1;
+        employer.clock_in(&mut employee, 1_100);
+        employer.update_pool(1_200);
+        assert_eq!(employer.claim_pool(&mut employee), 100 * 100);
+    }
+
+    #[test]
+    fn pool_ignores_updates_in_employee_mode() {
+        let mut employer = BOQEmployer { reward_mode: BOQRewardMode::Employee, ..pool_employer(1) };
+        employer.update_pool(1_100);
+        assert_eq!(employer.pool_slot, 1_000);
+        assert_eq!(employer.pool_reward_per_employee, 0);
+    }
+
+    #[test]
+    fn streak_starts_on_first_claim_and_grows_once_per_consecutive_shift() {
+        let employer = employer();
+        let mut employee = BOQEmployee { last_slot: 1_050, ..BOQEmployee::default() };
+        employee.update_streak(&employer, 1_080);
+        assert_eq!(employee.streak, 1);
+
+        employee.last_slot = 1_080;
+        employee.update_streak(&employer, 1_090);
+        assert_eq!(employee.streak, 1);
+
+        employee.last_slot = 1_090;
+        employee.update_streak(&employer, 1_150);
+        assert_eq!(employee.streak, 2);
+
+        employee.last_slot = 1_150;
+        employee.update_streak(&employer, 1_299);
+        assert_eq!(employee.streak, 3);
+    }
+
+    #[test]
+    fn streak_restarts_after_a_missed_shift() {
+        let employer = employer();
+        let mut employee = BOQEmployee { last_slot: 1_150, streak: 5, ..BOQEmployee::default() };
+        employee.update_streak(&employer, 1_300);
+        assert_eq!(employee.streak, 1);
+    }
+
+    #[test]
+    fn streak_multiplier_is_the_highest_tier_reached() {
+        let mut employer = employer();
+        employer.streak_tiers[0] = BOQStreakTier { streak: 3, multiplier: 500 };
+        employer.streak_tiers[1] = BOQStreakTier { streak: 10, multiplier: 2_000 };
+        employer.streak_tiers[2] = BOQStreakTier { streak: 7, multiplier: 1_000 };
+        assert_eq!(employer.streak_multiplier(0), 0);
+        assert_eq!(employer.streak_multiplier(2), 0);
+        assert_eq!(employer.streak_multiplier(3), 500);
+        assert_eq!(employer.streak_multiplier(8), 1_000);
+        assert_eq!(employer.streak_multiplier(u16::MAX), 2_000);
+    }
+
+    #[test]
+    fn level_counts_thresholds_reached_in_order() {
+        let mut employer = employer();
+        employer.level_tiers[0] = BOQLevelTier { slots: 100, multiplier: 500 };
+        employer.level_tiers[1] = BOQLevelTier { slots: 1_000, multiplier: 1_000 };
+        employer.level_tiers[3] = BOQLevelTier { slots: 2_000, multiplier: 3_000 };
+        assert_eq!(employer.level(0), 0);
+        assert_eq!(employer.level(99), 0);
+        assert_eq!(employer.level(100), 1);
+        assert_eq!(employer.level(999), 1);
+        assert_eq!(employer.level(1_000), 2);
+        // The empty third tier ends the table, so the fourth is never reached.
+        assert_eq!(employer.level(u64::MAX), 2);
+    }
+
+    #[test]
+    fn level_multiplier_is_the_multiplier_of_the_level_tier() {
+        let mut employer = employer();
+        employer.level_tiers[0] = BOQLevelTier { slots: 100, multiplier: 500 };
+        employer.level_tiers[1] = BOQLevelTier { slots: 1_000, multiplier: 1_000 };
+        assert_eq!(employer.level_multiplier(0), 0);
+        assert_eq!(employer.level_multiplier(1), 500);
+        assert_eq!(employer.level_multiplier(2), 1_000);
+        assert_eq!(employer.level_multiplier(BOQEmployer::MAX_LEVELS as u8 + 1), 0);
+    }
+
+    #[test]
+    fn milestones_are_granted_once_when_reached() {
+        let mut employer = employer();
+        employer.milestones[0] = BOQMilestone { slots: 100, bonus: 10 };
+        employer.milestones[2] = BOQMilestone { slots: 1_000, bonus: 200 };
+        employer.milestones[3] = BOQMilestone { slots: 500, bonus: 50 };
+        let mut employee = BOQEmployee::default();
+        assert_eq!(employee.grant_milestones(&employer), 0);
+
+        employee.total_slots = 600;
+        assert_eq!(employee.grant_milestones(&employer), 10 + 50);
+        assert_eq!(employee.milestones, 0b1001);
+        assert_eq!(employee.grant_milestones(&employer), 0);
+
+        employee.total_slots = 1_000;
+        assert_eq!(employee.grant_milestones(&employer), 200);
+        assert_eq!(employee.milestones, 0b1101);
+        assert_eq!(employee.grant_milestones(&employer), 0);
+    }
+}